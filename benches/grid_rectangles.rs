@@ -0,0 +1,59 @@
+//! Benchmarks of the level colliders merging on large grids
+//!
+//! Run with `cargo bench --bench grid_rectangles`
+
+#[path = "../src/utils/grid_rectangles.rs"]
+mod grid_rectangles;
+
+use bevy_ecs_ldtk::prelude::GridCoords;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use grid_rectangles::grid_rectangles;
+use std::{collections::HashSet, hint::black_box};
+
+/// Cells of a cave like level: a border, floors every 8 rows, and some holes
+fn level_cells(size: i32) -> HashSet<GridCoords> {
+    (0..size)
+        .flat_map(|y| (0..size).map(move |x| GridCoords { x, y }))
+        .filter(|&GridCoords { x, y }| {
+            let border = x == 0 || y == 0 || x == size - 1 || y == size - 1;
+            let floor = y % 8 == 0 && (x * 7 + y) % 13 != 0;
+            border || floor
+        })
+        .collect()
+}
+
+/// Pseudo random cells, the worst case to merge rectangles
+fn noise_cells(size: i32) -> HashSet<GridCoords> {
+    let mut seed = 0x2545_f491_u32;
+    (0..size)
+        .flat_map(|y| (0..size).map(move |x| GridCoords { x, y }))
+        .filter(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed % 2 == 0
+        })
+        .collect()
+}
+
+fn bench_grid_rectangles(c: &mut Criterion) {
+    let mut group = c.benchmark_group("grid_rectangles");
+    for size in [64, 256, 1024] {
+        let full = (0..size)
+            .flat_map(|y| (0..size).map(move |x| GridCoords { x, y }))
+            .collect::<HashSet<_>>();
+        for (name, cells) in [
+            ("full", full),
+            ("level", level_cells(size)),
+            ("noise", noise_cells(size)),
+        ] {
+            group.bench_with_input(BenchmarkId::new(name, size), &cells, |b, cells| {
+                b.iter(|| grid_rectangles(black_box(cells), size, size))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_grid_rectangles);
+criterion_main!(benches);
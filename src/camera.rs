@@ -1,89 +1,312 @@
 use crate::{
-    components::player::Player,
-    schedule::{InGameSet, InGameState},
+    components::{
+        character::Facing,
+        juice::CameraShake,
+        level::{LevelTransition, LevelTransitionKind},
+        player::Player,
+    },
+    schedule::InGameState,
 };
-use bevy::prelude::*;
-use bevy_ecs_ldtk::prelude::*;
+use bevy::{prelude::*, render::camera::ScalingMode};
+use bevy_ecs_ldtk::{ldtk::Level, prelude::*};
 
 pub fn camera_plugin(app: &mut App) {
-    app.add_systems(Startup, spawn_camera)
+    app.register_type::<CameraFollow>()
+        .add_systems(Startup, spawn_camera)
         .add_systems(
             Update,
-            (camera_fit_inside_current_level).run_if(in_state(InGameState::LevelLoaded)),
+            camera_follow_player
+                .run_if(in_state(InGameState::LevelLoaded).or(in_state(InGameState::Running))),
         )
         .add_systems(
-            Update,
-            (camera_fit_inside_current_level).in_set(InGameSet::EntityUpdate),
+            OnEnter(InGameState::LevelTransition),
+            start_level_transition,
         )
         .add_systems(
             Update,
-            (camera_fit_inside_current_level).in_set(InGameSet::EntityUpdate),
-        );
+            camera_level_transition.run_if(in_state(InGameState::LevelTransition)),
+        )
+        .add_systems(OnExit(InGameState::LevelTransition), end_level_transition);
 }
 
 const ASPECT_RATIO: f32 = 16. / 9.;
-// const CAM_LERP_FACTOR: f32 = 2.;
+
+const DEFAULT_LERP: f32 = 5.;
+const DEFAULT_DEAD_ZONE_X: f32 = 16.;
+const DEFAULT_DEAD_ZONE_Y: f32 = 24.;
+const DEFAULT_LOOK_AHEAD: f32 = 32.;
+
+/// How the camera follows the player.
+///
+/// The defaults can be overridden in a level with the LDtk fields `camera_lerp`,
+/// `camera_dead_zone_x`, `camera_dead_zone_y`, `camera_look_ahead` and `camera_view_height`.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component)]
+pub struct CameraFollow {
+    /// Speed the camera catches up with the player, the higher the snappier
+    pub lerp: f32,
+    /// Half size of the area around the camera center where the player moves
+    /// without moving the camera, in pixels
+    pub dead_zone: Vec2,
+    /// Distance the camera looks ahead of the player, in the direction it faces
+    pub look_ahead: f32,
+    /// Height of the view in pixels, or the whole level fitting the screen if `None`
+    pub view_height: Option<f32>,
+}
+
+impl Default for CameraFollow {
+    fn default() -> Self {
+        CameraFollow {
+            lerp: DEFAULT_LERP,
+            dead_zone: Vec2::new(DEFAULT_DEAD_ZONE_X, DEFAULT_DEAD_ZONE_Y),
+            look_ahead: DEFAULT_LOOK_AHEAD,
+            view_height: None,
+        }
+    }
+}
+
+impl CameraFollow {
+    /// These parameters, overridden by the fields of a level
+    fn with_level_fields(&self, level: &Level) -> Self {
+        let field = |name, default| level.get_float_field(name).copied().unwrap_or(default);
+        CameraFollow {
+            lerp: field("camera_lerp", self.lerp),
+            dead_zone: Vec2::new(
+                field("camera_dead_zone_x", self.dead_zone.x),
+                field("camera_dead_zone_y", self.dead_zone.y),
+            ),
+            look_ahead: field("camera_look_ahead", self.look_ahead),
+            view_height: level
+                .get_float_field("camera_view_height")
+                .ok()
+                .copied()
+                .or(self.view_height),
+        }
+    }
+
+    /// Size of the view in a level
+    fn view_size(&self, level_size: Vec2) -> Vec2 {
+        if let Some(height) = self.view_height {
+            Vec2::new(height * ASPECT_RATIO, height)
+        } else if level_size.x / level_size.y > ASPECT_RATIO {
+            // level is wider than the screen
+            let height = (level_size.y / 9.).round() * 9.;
+            Vec2::new(height * ASPECT_RATIO, height)
+        } else {
+            // level is taller than the screen
+            let width = (level_size.x / 16.).round() * 16.;
+            Vec2::new(width, width / ASPECT_RATIO)
+        }
+    }
+
+    /// Where the camera center should go to follow the `target`, keeping it in the dead zone
+    fn follow(&self, center: Vec2, target: Vec2) -> Vec2 {
+        let offset = target - center;
+        let excess = offset.abs() - self.dead_zone;
+        center + offset.signum() * excess.max(Vec2::ZERO)
+    }
+}
 
 fn spawn_camera(mut commands: Commands) {
-    let projection = OrthographicProjection::default_2d();
+    let projection = OrthographicProjection {
+        viewport_origin: Vec2::splat(0.5),
+        ..OrthographicProjection::default_2d()
+    };
     commands.spawn((
         Name::new("Camera"),
         Camera2d,
         Projection::Orthographic(projection),
+        CameraFollow::default(),
+        CameraShake::default(),
     ));
 }
 
-fn camera_fit_inside_current_level(
-    mut cameras: Query<(&mut Projection, &mut Transform), (With<Camera2d>, Without<Player>)>,
-    players: Query<&Transform, With<Player>>,
+/// Keep the camera center inside of the level, or centered on an axis where
+/// the level is smaller than the view
+fn clamp_in_level(center: Vec2, view_size: Vec2, level_size: Vec2) -> Vec2 {
+    let half = view_size / 2.;
+    let clamp = |value: f32, half: f32, size: f32| {
+        if half * 2. >= size {
+            size / 2.
+        } else {
+            value.clamp(half, size - half)
+        }
+    };
+    Vec2::new(
+        clamp(center.x, half.x, level_size.x),
+        clamp(center.y, half.y, level_size.y),
+    )
+}
+
+/// The selected level, in world coordinates
+struct SelectedLevel {
+    origin: Vec2,
+    size: Vec2,
+    /// Camera parameters in this level
+    follow: CameraFollow,
+}
+
+impl SelectedLevel {
+    fn find(
+        level_query: &Query<(&Transform, &LevelIid), (Without<Camera2d>, Without<Player>)>,
+        ldtk_project: &LdtkProject,
+        level_selection: &LevelSelection,
+        follow: &CameraFollow,
+    ) -> Option<Self> {
+        level_query.iter().find_map(|(level_transform, level_iid)| {
+            let level = ldtk_project.get_raw_level_by_iid(&level_iid.to_string())?;
+            level_selection
+                .is_match(&LevelIndices::default(), level)
+                .then(|| SelectedLevel {
+                    origin: level_transform.translation.xy(),
+                    size: Vec2::new(level.px_wid as f32, level.px_hei as f32),
+                    follow: follow.with_level_fields(level),
+                })
+        })
+    }
+
+    fn view_size(&self) -> Vec2 {
+        self.follow.view_size(self.size)
+    }
+
+    /// Position of the player relative to the level, looking ahead
+    fn target(&self, player_transform: &Transform, facing: &Facing) -> Vec2 {
+        let look_ahead = match facing {
+            Facing::Right => self.follow.look_ahead,
+            Facing::Left => -self.follow.look_ahead,
+        };
+        player_transform.translation.xy() - self.origin + Vec2::X * look_ahead
+    }
+
+    /// World position of the camera centered on `center`, relative to the level
+    fn camera_position(&self, center: Vec2) -> Vec2 {
+        clamp_in_level(center, self.view_size(), self.size) + self.origin
+    }
+}
+
+fn set_view_size(projection: &mut Projection, view_size: Vec2) -> Result {
+    let Projection::Orthographic(orthographic_projection) = projection else {
+        return Err(BevyError::from("non-orthographic projection found"));
+    };
+    orthographic_projection.scaling_mode = ScalingMode::Fixed {
+        width: view_size.x,
+        height: view_size.y,
+    };
+    Ok(())
+}
+
+fn camera_follow_player(
+    mut cameras: Query<
+        (&mut Projection, &mut Transform, &CameraFollow),
+        (With<Camera2d>, Without<Player>),
+    >,
+    players: Query<(&Transform, &Facing), With<Player>>,
     level_query: Query<(&Transform, &LevelIid), (Without<Camera2d>, Without<Player>)>,
     ldtk_projects: Query<&LdtkProjectHandle>,
     level_selection: Res<LevelSelection>,
     ldtk_project_assets: Res<Assets<LdtkProject>>,
+    in_game_state: Res<State<InGameState>>,
+    time: Res<Time>,
 ) -> Result {
-    let player_transform = players.single()?;
-    let player_translation = player_transform.translation;
+    let (player_transform, facing) = players.single()?;
+    let (mut projection, mut camera_transform, follow) = cameras.single_mut()?;
+    let ldtk_project = ldtk_project_assets
+        .get(ldtk_projects.single()?)
+        .ok_or("Project should be loaded if level has spawned")?;
+    let Some(level) = SelectedLevel::find(&level_query, ldtk_project, &level_selection, follow)
+    else {
+        return Ok(());
+    };
+    set_view_size(&mut projection, level.view_size())?;
 
-    let (mut projection, mut camera_transform) = cameras.single_mut()?;
-    let Projection::Orthographic(ref mut orthographic_projection) = &mut *projection else {
-        return Err(BevyError::from("non-orthographic projection found"));
+    // Positions relative to the level
+    let target = level.target(player_transform, facing);
+    let center = camera_transform.translation.xy() - level.origin;
+    let center = if *in_game_state.get() == InGameState::LevelLoaded {
+        // Start the level with the camera on the player
+        target
+    } else {
+        let factor = 1. - (-level.follow.lerp * time.delta_secs()).exp();
+        center.lerp(level.follow.follow(center, target), factor)
     };
+    camera_transform.translation = level
+        .camera_position(center)
+        .extend(camera_transform.translation.z);
+    Ok(())
+}
+
+/// Where the camera was when a [LevelTransition] started
+#[derive(Component)]
+struct TransitionStart {
+    position: Vec2,
+    view_size: Vec2,
+}
+
+fn start_level_transition(
+    mut commands: Commands,
+    cameras: Query<(Entity, &Projection, &Transform), With<Camera2d>>,
+) {
+    for (entity, projection, transform) in &cameras {
+        let view_size = match projection {
+            Projection::Orthographic(OrthographicProjection {
+                scaling_mode: ScalingMode::Fixed { width, height },
+                ..
+            }) => Vec2::new(*width, *height),
+            _ => continue,
+        };
+        commands.entity(entity).insert(TransitionStart {
+            position: transform.translation.xy(),
+            view_size,
+        });
+    }
+}
+
+/// Move the camera from the previous level to the player in the new one
+fn camera_level_transition(
+    mut cameras: Query<
+        (
+            &mut Projection,
+            &mut Transform,
+            &CameraFollow,
+            &TransitionStart,
+        ),
+        (With<Camera2d>, Without<Player>),
+    >,
+    players: Query<(&Transform, &Facing), With<Player>>,
+    level_query: Query<(&Transform, &LevelIid), (Without<Camera2d>, Without<Player>)>,
+    ldtk_projects: Query<&LdtkProjectHandle>,
+    level_selection: Res<LevelSelection>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    transition: Res<LevelTransition>,
+) -> Result {
+    let (player_transform, facing) = players.single()?;
+    let (mut projection, mut camera_transform, follow, start) = cameras.single_mut()?;
     let ldtk_project = ldtk_project_assets
         .get(ldtk_projects.single()?)
         .ok_or("Project should be loaded if level has spawned")?;
+    let Some(level) = SelectedLevel::find(&level_query, ldtk_project, &level_selection, follow)
+    else {
+        return Ok(());
+    };
+    let end_position = level.camera_position(level.target(player_transform, facing));
 
-    for (level_transform, level_iid) in &level_query {
-        let level = ldtk_project
-            .get_raw_level_by_iid(&level_iid.to_string())
-            .ok_or("Spawned level should exist in LDtk project")?;
-        if level_selection.is_match(&LevelIndices::default(), level) {
-            let level_ratio = level.px_wid as f32 / level.px_hei as f32;
-            orthographic_projection.viewport_origin = Vec2::ZERO;
-            if level_ratio > ASPECT_RATIO {
-                // level is wider than the screen
-                let height = (level.px_hei as f32 / 9.).round() * 9.;
-                let width = height * ASPECT_RATIO;
-                orthographic_projection.scaling_mode =
-                    bevy::render::camera::ScalingMode::Fixed { width, height };
-                camera_transform.translation.x =
-                    (player_translation.x - level_transform.translation.x - width / 2.)
-                        .clamp(0., level.px_wid as f32 - width);
-                camera_transform.translation.y = 0.;
-            } else {
-                // level is taller than the screen
-                let width = (level.px_wid as f32 / 16.).round() * 16.;
-                let height = width / ASPECT_RATIO;
-                orthographic_projection.scaling_mode =
-                    bevy::render::camera::ScalingMode::Fixed { width, height };
-                camera_transform.translation.y =
-                    (player_translation.y - level_transform.translation.y - height / 2.)
-                        .clamp(0., level.px_hei as f32 - height);
-                camera_transform.translation.x = 0.;
-            }
-
-            camera_transform.translation.x += level_transform.translation.x;
-            camera_transform.translation.y += level_transform.translation.y;
-        }
-    }
+    let fraction = match transition.kind {
+        LevelTransitionKind::Slide => transition.timer.fraction(),
+        // Switch while the screen is black
+        LevelTransitionKind::Fade if transition.timer.fraction() < 0.5 => 0.,
+        LevelTransitionKind::Fade => 1.,
+    };
+    let t = fraction * fraction * (3. - 2. * fraction);
+    set_view_size(&mut projection, start.view_size.lerp(level.view_size(), t))?;
+    camera_transform.translation = start
+        .position
+        .lerp(end_position, t)
+        .extend(camera_transform.translation.z);
     Ok(())
 }
+
+fn end_level_transition(mut commands: Commands, cameras: Query<Entity, With<TransitionStart>>) {
+    for entity in &cameras {
+        commands.entity(entity).remove::<TransitionStart>();
+    }
+}
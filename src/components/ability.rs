@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+
+/// A movement ability, granted by an [Item](crate::components::item::Item).
+///
+/// As abilities come from items, a door that expects an item also expects its ability.
+/// Opening the door doesn't use up the item, so the ability is kept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub enum Ability {
+    /// Jump higher
+    HighJump,
+    /// Jump once more while in the air
+    DoubleJump,
+    /// Quickly move horizontally, being invulnerable during the move
+    Dash,
+    /// Slide along walls, and jump from them
+    WallJump,
+}
+
+/// Is the [Ability::DoubleJump] still available before landing
+#[derive(Component, Clone, Copy, Default, Debug, Deref, DerefMut, Reflect)]
+pub struct AirJump(pub bool);
+
+const DASH_COOLDOWN_SECS: f32 = 0.6;
+pub const DASH_DURATION_SECS: f32 = 0.15;
+
+/// Delay between two [Ability::Dash]
+#[derive(Component, Deref, DerefMut)]
+pub struct DashCooldown(pub Timer);
+
+impl Default for DashCooldown {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(DASH_COOLDOWN_SECS, TimerMode::Once);
+        // A character should be able to dash right away
+        timer.tick(timer.duration());
+        DashCooldown(timer)
+    }
+}
+
+/// Marker of a character currently dashing, until the timer finishes
+#[derive(Component, Deref, DerefMut)]
+#[component(storage = "SparseSet")]
+pub struct Dashing(pub Timer);
+
+impl Default for Dashing {
+    fn default() -> Self {
+        Dashing(Timer::from_seconds(DASH_DURATION_SECS, TimerMode::Once))
+    }
+}
+
+/// Marker of a character that just jumped from a wall: the horizontal input is
+/// ignored until the timer finishes, so it can't stick back to the wall immediately
+#[derive(Component, Deref, DerefMut)]
+#[component(storage = "SparseSet")]
+pub struct WallJumping(pub Timer);
+
+impl Default for WallJumping {
+    fn default() -> Self {
+        WallJumping(Timer::from_seconds(0.15, TimerMode::Once))
+    }
+}
@@ -0,0 +1,237 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Sprite sheet of a `.clips.ron` file, a grid of square images
+#[derive(Deserialize)]
+struct SheetDef {
+    image: String,
+    tile_size: u32,
+    columns: u32,
+    rows: u32,
+    #[serde(default)]
+    padding: u32,
+    #[serde(default)]
+    offset: u32,
+}
+
+/// How a [Clip] plays
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Reflect)]
+pub enum ClipMode {
+    #[default]
+    Loop,
+    /// Stay on the last frame, see [ClipFinishedEvent]
+    Once,
+}
+
+#[derive(Deserialize)]
+struct ClipDef {
+    sheet: String,
+    frames: Vec<usize>,
+    #[serde(default = "default_fps")]
+    fps: f32,
+    #[serde(default)]
+    mode: ClipMode,
+}
+
+fn default_fps() -> f32 {
+    10.
+}
+
+#[derive(Deserialize)]
+struct SpriteClipsDef {
+    sheets: HashMap<String, SheetDef>,
+    clips: HashMap<String, ClipDef>,
+}
+
+/// A named animation of a [SpriteClips]
+#[derive(Clone, Debug, Reflect)]
+pub struct Clip {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    /// Indices of the frames in the layout
+    pub frames: Vec<usize>,
+    pub fps: f32,
+    pub mode: ClipMode,
+}
+
+impl Clip {
+    /// Index in [Clip::frames] shown after `secs`, and if a [ClipMode::Once] clip is over
+    fn frame_after(&self, secs: f32) -> (usize, bool) {
+        let frame = (secs * self.fps) as usize;
+        match self.mode {
+            ClipMode::Loop => (frame % self.frames.len(), false),
+            ClipMode::Once if frame >= self.frames.len() => (self.frames.len() - 1, true),
+            ClipMode::Once => (frame, false),
+        }
+    }
+
+    /// Index in [Clip::frames] at a `fraction` of the clip
+    fn frame_at(&self, fraction: f32) -> usize {
+        ((fraction * self.frames.len() as f32) as usize).min(self.frames.len() - 1)
+    }
+}
+
+/// Animations of a sprite, loaded from a `.clips.ron` file:
+///
+/// ```ron
+/// (
+///     sheets: {
+///         "walk": (image: "player/walk.png", tile_size: 16, columns: 8, rows: 4),
+///     },
+///     clips: {
+///         "walk_right": (sheet: "walk", frames: [0, 1, 2, 3], fps: 10, mode: Loop),
+///     },
+/// )
+/// ```
+#[derive(Asset, TypePath, Debug)]
+pub struct SpriteClips {
+    #[dependency]
+    images: Vec<Handle<Image>>,
+    clips: HashMap<String, Clip>,
+}
+
+impl SpriteClips {
+    pub fn get(&self, clip: &str) -> Option<&Clip> {
+        self.clips.get(clip)
+    }
+}
+
+#[derive(Default)]
+pub struct SpriteClipsLoader;
+
+impl AssetLoader for SpriteClipsLoader {
+    type Asset = SpriteClips;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<SpriteClips, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let def = ron::de::from_bytes::<SpriteClipsDef>(&bytes)?;
+
+        let mut sheets = HashMap::new();
+        for (name, sheet) in def.sheets {
+            let image = load_context.load(sheet.image);
+            let layout = load_context.add_labeled_asset(
+                format!("{name}_layout"),
+                TextureAtlasLayout::from_grid(
+                    UVec2::splat(sheet.tile_size),
+                    sheet.columns,
+                    sheet.rows,
+                    Some(UVec2::splat(sheet.padding)),
+                    Some(UVec2::splat(sheet.offset)),
+                ),
+            );
+            let len = (sheet.columns * sheet.rows) as usize;
+            sheets.insert(name, (image, layout, len));
+        }
+
+        let mut clips = HashMap::new();
+        for (name, clip) in def.clips {
+            let (image, layout, len) = sheets
+                .get(&clip.sheet)
+                .ok_or_else(|| format!("Clip {name}: unknown sheet {}", clip.sheet))?;
+            if clip.frames.is_empty() || clip.frames.iter().any(|frame| frame >= len) {
+                return Err(format!("Clip {name}: frames should be in 0..{len}").into());
+            }
+            if clip.fps <= 0. {
+                return Err(format!("Clip {name}: fps should be positive").into());
+            }
+            clips.insert(
+                name,
+                Clip {
+                    image: image.clone(),
+                    layout: layout.clone(),
+                    frames: clip.frames,
+                    fps: clip.fps,
+                    mode: clip.mode,
+                },
+            );
+        }
+
+        Ok(SpriteClips {
+            images: sheets.into_values().map(|(image, _, _)| image).collect(),
+            clips,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["clips.ron"]
+    }
+}
+
+/// Plays the clips of a [SpriteClips] on the [Sprite] of an entity.
+///
+/// The clip is chosen by the gameplay with [SpriteAnimation::play], or
+/// [SpriteAnimation::play_at] for animations that follow a timer.
+#[derive(Component, Debug, Reflect)]
+#[require(Sprite)]
+pub struct SpriteAnimation {
+    pub clips: Handle<SpriteClips>,
+    clip: String,
+    /// Seconds since the clip started
+    elapsed: f32,
+    /// Fraction of the clip to show, for clips following a timer
+    fraction: Option<f32>,
+    finished: bool,
+}
+
+impl SpriteAnimation {
+    pub fn new(clips: Handle<SpriteClips>, clip: &str) -> Self {
+        SpriteAnimation {
+            clips,
+            clip: clip.into(),
+            elapsed: 0.,
+            fraction: None,
+            finished: false,
+        }
+    }
+
+    pub fn clip(&self) -> &str {
+        &self.clip
+    }
+
+    /// Play a clip, from its start if it was not already playing
+    pub fn play(&mut self, clip: &str) {
+        if self.clip != clip {
+            self.clip = clip.into();
+            self.elapsed = 0.;
+            self.finished = false;
+        }
+        self.fraction = None;
+    }
+
+    /// Show a clip at a `fraction` of its frames, to follow a timer
+    pub fn play_at(&mut self, clip: &str, fraction: f32) {
+        self.play(clip);
+        self.fraction = Some(fraction);
+    }
+
+    /// Move the animation forward, returning the clip frame to show,
+    /// and if a [ClipMode::Once] clip just finished
+    pub fn tick(&mut self, clip: &Clip, delta_secs: f32) -> (usize, bool) {
+        if let Some(fraction) = self.fraction {
+            return (clip.frame_at(fraction), false);
+        }
+        let (frame, over) = clip.frame_after(self.elapsed);
+        self.elapsed += delta_secs;
+        let just_finished = over && !self.finished;
+        self.finished = over;
+        (frame, just_finished)
+    }
+}
+
+/// Event triggered on an entity when its [ClipMode::Once] clip has played all its frames
+#[derive(Event, Debug)]
+pub struct ClipFinishedEvent {
+    pub clip: String,
+}
@@ -0,0 +1,81 @@
+use crate::{
+    components::{
+        character::{Damage, Facing},
+        GROUP_ENEMY, GROUP_PLAYER, GROUP_PLAYER_ATTACK,
+    },
+    utils::despawn_after::DespawnAfter,
+};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use std::time::Duration;
+
+const ATTACK_COOLDOWN_SECS: f32 = 0.4;
+const ATTACK_DURATION_SECS: f32 = 0.2;
+const MELEE_REACH: f32 = 9.;
+const THROW_SPEED: f32 = 260.;
+const THROW_LIFT: f32 = 60.;
+
+/// Delay between two attacks of a character
+#[derive(Component, Deref, DerefMut)]
+pub struct AttackCooldown(pub Timer);
+
+impl Default for AttackCooldown {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(ATTACK_COOLDOWN_SECS, TimerMode::Once);
+        // A character should be able to attack right away
+        timer.tick(timer.duration());
+        AttackCooldown(timer)
+    }
+}
+
+/// Marker of a character currently attacking, until the timer finishes
+#[derive(Component, Deref, DerefMut)]
+#[component(storage = "SparseSet")]
+pub struct Attacking(pub Timer);
+
+impl Default for Attacking {
+    fn default() -> Self {
+        Attacking(Timer::from_seconds(ATTACK_DURATION_SECS, TimerMode::Once))
+    }
+}
+
+/// A short living [Sensor] in front of the player that hurts enemies
+#[derive(Component, Default)]
+pub struct MeleeHitbox;
+
+pub fn melee_hitbox(parent: Entity, facing: Facing) -> impl Bundle {
+    (
+        Name::new("MeleeHitbox"),
+        MeleeHitbox,
+        Damage(1),
+        ChildOf(parent),
+        Transform::from_xyz(facing.sign() * MELEE_REACH, 0., 0.),
+        Collider::cuboid(6., 5.),
+        Sensor,
+        ActiveEvents::COLLISION_EVENTS,
+        CollisionGroups::new(GROUP_PLAYER_ATTACK, GROUP_ENEMY),
+        DespawnAfter::new(Duration::from_secs_f32(ATTACK_DURATION_SECS)),
+    )
+}
+
+/// An item thrown by the player
+#[derive(Component, Default)]
+pub struct Projectile;
+
+pub fn projectile(translation: Vec3, facing: Facing, sprite: Sprite) -> impl Bundle {
+    (
+        Name::new("Projectile"),
+        Projectile,
+        Damage(2),
+        sprite,
+        Transform::from_translation(translation + Vec3::X * facing.sign() * MELEE_REACH),
+        RigidBody::Dynamic,
+        Velocity::linear(vec2(facing.sign() * THROW_SPEED, THROW_LIFT)),
+        GravityScale(0.5),
+        Collider::ball(3.),
+        Sensor,
+        ActiveEvents::COLLISION_EVENTS,
+        CollisionGroups::new(GROUP_PLAYER_ATTACK, !GROUP_PLAYER),
+        DespawnAfter::new(Duration::from_secs(2)),
+    )
+}
@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
-use std::collections::HashSet;
+use std::{collections::HashSet, time::Duration};
 
 pub trait Movement {
     fn is_moving_left(&self) -> bool;
@@ -26,6 +26,7 @@ impl Movement for Velocity {
 #[derive(Component, Clone, Copy, Default, Debug, Reflect)]
 pub struct Life {
     current: u16,
+    max: u16,
 }
 
 const DEFAULT_LIFE: i32 = 5;
@@ -38,6 +39,7 @@ impl From<&EntityInstance> for Life {
             .unwrap_or(DEFAULT_LIFE);
         Life {
             current: life as u16,
+            max: life as u16,
         }
     }
 }
@@ -47,6 +49,10 @@ impl Life {
         self.current
     }
 
+    pub fn max(&self) -> u16 {
+        self.max
+    }
+
     pub fn hit(&mut self, damage: u16) {
         self.current = self.current.saturating_sub(damage);
     }
@@ -90,6 +96,109 @@ impl From<&EntityInstance> for JumpSpeed {
     }
 }
 
+/// A float field of an LDtk entity, or `default` if it has none
+fn float_field(entity_instance: &EntityInstance, name: &str, default: f32) -> f32 {
+    entity_instance
+        .get_float_field(name)
+        .copied()
+        .unwrap_or(default)
+}
+
+/// Tunables that make jumps more forgiving and controllable
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+pub struct JumpControl {
+    /// Delay, in seconds, during which a character can still jump after leaving the ground
+    pub coyote_time: f32,
+    /// Delay, in seconds, during which a jump request is kept before landing
+    pub jump_buffer: f32,
+    /// Factor applied to the upward velocity when the jump button is released early
+    pub jump_cut: f32,
+}
+
+const DEFAULT_COYOTE_TIME: f32 = 0.1;
+const DEFAULT_JUMP_BUFFER: f32 = 0.12;
+const DEFAULT_JUMP_CUT: f32 = 0.45;
+
+impl Default for JumpControl {
+    fn default() -> Self {
+        JumpControl {
+            coyote_time: DEFAULT_COYOTE_TIME,
+            jump_buffer: DEFAULT_JUMP_BUFFER,
+            jump_cut: DEFAULT_JUMP_CUT,
+        }
+    }
+}
+
+impl From<&EntityInstance> for JumpControl {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        JumpControl {
+            coyote_time: float_field(entity_instance, "coyote_time", DEFAULT_COYOTE_TIME),
+            jump_buffer: float_field(entity_instance, "jump_buffer", DEFAULT_JUMP_BUFFER),
+            jump_cut: float_field(entity_instance, "jump_cut", DEFAULT_JUMP_CUT),
+        }
+    }
+}
+
+/// Elapsed times used by [JumpControl]
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+pub struct JumpTimings {
+    /// Seconds since the character could jump for the last time
+    pub since_grounded: f32,
+    /// Seconds since the jump button was pressed for the last time
+    pub since_jump_requested: f32,
+}
+
+impl Default for JumpTimings {
+    fn default() -> Self {
+        JumpTimings {
+            since_grounded: f32::INFINITY,
+            since_jump_requested: f32::INFINITY,
+        }
+    }
+}
+
+/// Heights, in pixels, of the falls that hurt or kill a character
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+pub struct FallDamage {
+    pub damage_height: f32,
+    pub death_height: f32,
+}
+
+const DEFAULT_FALL_DAMAGE_HEIGHT: f32 = 96.;
+const DEFAULT_FALL_DEATH_HEIGHT: f32 = 256.;
+
+impl Default for FallDamage {
+    fn default() -> Self {
+        FallDamage {
+            damage_height: DEFAULT_FALL_DAMAGE_HEIGHT,
+            death_height: DEFAULT_FALL_DEATH_HEIGHT,
+        }
+    }
+}
+
+impl From<&EntityInstance> for FallDamage {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        FallDamage {
+            damage_height: float_field(
+                entity_instance,
+                "fall_damage_height",
+                DEFAULT_FALL_DAMAGE_HEIGHT,
+            ),
+            death_height: float_field(
+                entity_instance,
+                "fall_death_height",
+                DEFAULT_FALL_DEATH_HEIGHT,
+            ),
+        }
+    }
+}
+
+/// The highest position reached since the character left the ground, if falling
+#[derive(Component, Clone, Copy, Default, Debug, Reflect)]
+pub struct FallTracker {
+    pub peak_y: Option<f32>,
+}
+
 #[derive(Clone, Copy, Component)]
 pub struct Damage(pub u16);
 
@@ -121,29 +230,89 @@ pub struct GroundDetection {
     pub on_ground: bool,
 }
 
+#[derive(Component, Debug)]
+pub struct WallSensor {
+    pub wall_detection_entity: Entity,
+    pub side: Facing,
+    pub intersecting_wall_entities: HashSet<Entity>,
+}
+
+pub fn wall_sensor(parent: Entity, half_extents: Vec2, side: Facing) -> impl Bundle {
+    let pos = Vec3::new(side.sign() * half_extents.x, 0., 0.);
+
+    (
+        Name::new("WallSensor"),
+        WallSensor {
+            wall_detection_entity: parent,
+            side,
+            intersecting_wall_entities: HashSet::new(),
+        },
+        ChildOf(parent),
+        Transform::from_translation(pos),
+        Collider::cuboid(1., half_extents.y / 2.0),
+        ActiveEvents::COLLISION_EVENTS,
+        Sensor,
+    )
+}
+
+#[derive(Clone, Default, Component, Debug, Reflect)]
+pub struct WallDetection {
+    pub on_left: bool,
+    pub on_right: bool,
+}
+
+impl WallDetection {
+    pub fn on_wall(&self, side: Facing) -> bool {
+        match side {
+            Facing::Left => self.on_left,
+            Facing::Right => self.on_right,
+        }
+    }
+}
+
 #[derive(Component, Default, Clone, Eq, PartialEq, Debug, Reflect)]
 pub struct Climber {
     pub climbing: bool,
     pub intersecting_climbables: HashSet<Entity>,
 }
 
+#[derive(Component, Clone, Copy)]
+#[component(storage = "SparseSet")]
+pub struct Dying;
+
+/// A character that has just been hurt, and can't be controlled until the timer finishes
 #[derive(Component, Deref, DerefMut)]
-pub struct AnimationTimer(pub Timer);
+#[component(storage = "SparseSet")]
+pub struct HitStun(pub Timer);
 
-impl Default for AnimationTimer {
-    fn default() -> Self {
-        AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating))
+impl HitStun {
+    pub fn new(duration: Duration) -> Self {
+        HitStun(Timer::new(duration, TimerMode::Once))
     }
 }
 
-#[derive(Component, Clone, Copy)]
-#[component(storage = "SparseSet")]
-pub struct Dying;
-
-#[derive(Component, Clone, Copy, Default, Reflect, Deref)]
+#[derive(Component, Clone, Copy, Default, Eq, PartialEq, Reflect, Deref)]
 #[component(storage = "SparseSet")]
 pub struct InWater(pub bool);
 
 #[derive(Component, Clone, Copy, Default, Reflect, Deref)]
 #[component(storage = "SparseSet")]
 pub struct Jumping(pub bool);
+
+/// The horizontal direction a character is looking at
+#[derive(Component, Clone, Copy, Default, Debug, Eq, PartialEq, Reflect)]
+pub enum Facing {
+    Left,
+    #[default]
+    Right,
+}
+
+impl Facing {
+    /// `-1.` when facing left, `1.` when facing right
+    pub fn sign(&self) -> f32 {
+        match self {
+            Facing::Left => -1.,
+            Facing::Right => 1.,
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::components::{
+    animation::SpriteClips,
     character::{Damage, Life, Speed},
     GROUP_ENEMY,
 };
@@ -6,6 +7,21 @@ use bevy::prelude::*;
 use bevy_ecs_ldtk::{prelude::*, utils::ldtk_pixel_coords_to_translation_pivoted};
 use bevy_rapier2d::prelude::*;
 
+/// All [Enemy] assets
+#[derive(Resource, Clone, Asset, TypePath)]
+pub struct EnemyAssets {
+    #[dependency]
+    pub animations: Handle<SpriteClips>,
+}
+
+impl FromWorld for EnemyAssets {
+    fn from_world(world: &mut World) -> Self {
+        EnemyAssets {
+            animations: world.load_asset("animations/mob.clips.ron"),
+        }
+    }
+}
+
 #[derive(Component, Copy, Clone, Eq, PartialEq, Debug, Default)]
 #[require(
     Name::new("Enemy"),
@@ -87,3 +103,10 @@ pub struct LdtkMobBundle {
     #[ldtk_entity]
     patrol: Patrol,
 }
+
+/// Clip of the [Enemy] animation played once when it dies
+pub const ENEMY_DEATH_CLIP: &str = "death";
+
+/// Triggered on an [Enemy] when it is killed
+#[derive(Event)]
+pub struct EnemyDeathEvent;
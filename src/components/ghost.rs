@@ -0,0 +1,70 @@
+use crate::storage;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// Transparency of the [Ghost]
+pub const GHOST_ALPHA: f32 = 0.4;
+
+/// The player sprite at a game step
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GhostFrame {
+    pub translation: [f32; 2],
+    /// Clip of the player animation, see [PlayerAssets](crate::components::player::PlayerAssets)
+    pub clip: String,
+    /// Index of the image in the layout of the clip
+    pub index: usize,
+}
+
+/// Player sprites of a run of a level, for each game step
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct GhostRun {
+    /// Identifier of the LDtk level where the run started
+    pub level: String,
+    pub frames: Vec<GhostFrame>,
+}
+
+impl GhostRun {
+    fn file_name(level: &str) -> String {
+        format!("ghost_{level}.ron")
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        Ok(ron::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Fastest run of a level, if any
+    pub fn load_best(level: &str) -> Option<Self> {
+        let run = storage::load::<GhostRun>(&Self::file_name(level));
+        (!run.frames.is_empty()).then_some(run)
+    }
+
+    pub fn save_best(&self) -> Result {
+        storage::save(&Self::file_name(&self.level), self)
+    }
+}
+
+/// Player sprites of the current run
+#[derive(Resource, Default)]
+pub struct GhostRecording(pub GhostRun);
+
+/// Run given with `--ghost <file>`, to race against instead of the fastest run of its level
+#[derive(Resource)]
+pub struct LoadedGhost(pub GhostRun);
+
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct GhostSettings {
+    pub enabled: bool,
+}
+
+impl Default for GhostSettings {
+    fn default() -> Self {
+        GhostSettings { enabled: true }
+    }
+}
+
+/// A translucent copy of the player, replaying a previous run
+#[derive(Component)]
+#[require(Name::new("Ghost"), Sprite)]
+pub struct Ghost(pub GhostRun);
@@ -0,0 +1,104 @@
+use crate::storage;
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{ldtk::Level, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const HIGH_SCORES_FILE: &str = "high_scores.ron";
+/// Version of the high scores file format
+const HIGH_SCORES_VERSION: u32 = 1;
+pub const MAX_HIGH_SCORES: usize = 10;
+pub const MAX_NAME_LEN: usize = 12;
+
+const DEFAULT_LEVEL_VERSION: i32 = 1;
+
+/// Version of a level, to bump in LDtk when a change makes previous scores meaningless
+pub fn level_version(level: &Level) -> i32 {
+    level
+        .get_int_field("version")
+        .copied()
+        .unwrap_or(DEFAULT_LEVEL_VERSION)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+}
+
+/// Best scores of a level, from the best to the worst
+#[derive(Clone, Default, Debug, Serialize, Deserialize, Reflect)]
+pub struct LevelHighScores {
+    pub level_version: i32,
+    pub entries: Vec<HighScore>,
+}
+
+/// Best scores of all levels, saved between game sessions
+#[derive(Resource, Clone, Debug, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct HighScores {
+    version: u32,
+    /// High scores by level identifier
+    pub levels: BTreeMap<String, LevelHighScores>,
+}
+
+impl Default for HighScores {
+    fn default() -> Self {
+        HighScores {
+            version: HIGH_SCORES_VERSION,
+            levels: BTreeMap::new(),
+        }
+    }
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        let high_scores = storage::load::<HighScores>(HIGH_SCORES_FILE);
+        if high_scores.version == HIGH_SCORES_VERSION {
+            high_scores
+        } else {
+            warn!(
+                "Ignore high scores of unsupported version {}",
+                high_scores.version
+            );
+            HighScores::default()
+        }
+    }
+
+    pub fn save(&self) -> Result {
+        storage::save(HIGH_SCORES_FILE, self)
+    }
+
+    /// High scores of a version of a level
+    pub fn entries(&self, level: &str, level_version: i32) -> &[HighScore] {
+        self.levels
+            .get(level)
+            .filter(|level_high_scores| level_high_scores.level_version == level_version)
+            .map(|level_high_scores| level_high_scores.entries.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Check if a score enters the high scores of a level
+    pub fn qualifies(&self, level: &str, level_version: i32, score: u32) -> bool {
+        let entries = self.entries(level, level_version);
+        entries.len() < MAX_HIGH_SCORES || entries.last().is_some_and(|last| score > last.score)
+    }
+
+    /// Add a score to the high scores of a level.
+    ///
+    /// Scores of previous versions of the level are dropped.
+    pub fn insert(&mut self, level: &str, level_version: i32, high_score: HighScore) {
+        let level_high_scores = self.levels.entry(level.to_string()).or_default();
+        if level_high_scores.level_version != level_version {
+            *level_high_scores = LevelHighScores {
+                level_version,
+                entries: Vec::new(),
+            };
+        }
+        let entries = &mut level_high_scores.entries;
+        let index = entries.partition_point(|entry| entry.score >= high_score.score);
+        entries.insert(index, high_score);
+        entries.truncate(MAX_HIGH_SCORES);
+    }
+}
@@ -0,0 +1,214 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Actions of the player
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum GameKey {
+    Left,
+    Right,
+    Up,
+    Down,
+    Jump,
+    DigLeft,
+    DigRight,
+    Attack,
+    Throw,
+    Dash,
+    Restart,
+}
+
+impl GameKey {
+    pub const ALL: [GameKey; 11] = [
+        GameKey::Left,
+        GameKey::Right,
+        GameKey::Up,
+        GameKey::Down,
+        GameKey::Jump,
+        GameKey::DigLeft,
+        GameKey::DigRight,
+        GameKey::Attack,
+        GameKey::Throw,
+        GameKey::Dash,
+        GameKey::Restart,
+    ];
+
+    /// Key of the keyboard bound to the action by default, see [KeyBindings]
+    pub fn key_code(self) -> KeyCode {
+        match self {
+            GameKey::Left => KeyCode::KeyA,
+            GameKey::Right => KeyCode::KeyD,
+            GameKey::Up => KeyCode::KeyW,
+            GameKey::Down => KeyCode::KeyS,
+            GameKey::Jump => KeyCode::Space,
+            GameKey::DigLeft => KeyCode::KeyQ,
+            GameKey::DigRight => KeyCode::KeyE,
+            GameKey::Attack => KeyCode::KeyF,
+            GameKey::Throw => KeyCode::KeyC,
+            GameKey::Dash => KeyCode::ShiftLeft,
+            GameKey::Restart => KeyCode::KeyR,
+        }
+    }
+
+    fn mask(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+/// Keys that can be bound to a [GameKey], except the ones used by the game itself,
+/// like T that shows the speedrun splits
+pub const BINDABLE_KEYS: [KeyCode; 49] = [
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::ArrowUp,
+    KeyCode::ArrowDown,
+    KeyCode::Space,
+    KeyCode::Enter,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+];
+
+/// Name of a [bindable](BINDABLE_KEYS) key, as saved in the settings
+pub fn key_name(key_code: KeyCode) -> String {
+    format!("{key_code:?}")
+}
+
+/// A [bindable](BINDABLE_KEYS) key from its [name](key_name)
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS
+        .into_iter()
+        .find(|&key_code| key_name(key_code) == name)
+}
+
+/// Keys of the keyboard bound to the [GameKey]s
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct KeyBindings {
+    keys: [KeyCode; GameKey::ALL.len()],
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            keys: GameKey::ALL.map(GameKey::key_code),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn key_code(&self, key: GameKey) -> KeyCode {
+        self.keys[key as usize]
+    }
+
+    pub fn bind(&mut self, key: GameKey, key_code: KeyCode) {
+        self.keys[key as usize] = key_code;
+    }
+}
+
+/// A set of [GameKey]s
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub struct GameKeys(u16);
+
+impl GameKeys {
+    pub fn contains(self, key: GameKey) -> bool {
+        self.0 & key.mask() != 0
+    }
+
+    pub fn set(&mut self, key: GameKey, value: bool) {
+        if value {
+            self.0 |= key.mask();
+        } else {
+            self.0 &= !key.mask();
+        }
+    }
+}
+
+/// State of the [GameKey]s for a step of the game.
+///
+/// Gameplay systems read it instead of the keyboard, so a game can be recorded and replayed.
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct GameInput {
+    pressed: GameKeys,
+    just_pressed: GameKeys,
+    just_released: GameKeys,
+}
+
+impl GameInput {
+    pub fn pressed(&self, key: GameKey) -> bool {
+        self.pressed.contains(key)
+    }
+
+    pub fn just_pressed(&self, key: GameKey) -> bool {
+        self.just_pressed.contains(key)
+    }
+
+    pub fn just_released(&self, key: GameKey) -> bool {
+        self.just_released.contains(key)
+    }
+
+    /// Update from the keyboard.
+    ///
+    /// Presses and releases are kept until the next game step [consume]s them,
+    /// as there may be several frames between two steps.
+    ///
+    /// [consume]: GameInput::consume
+    pub fn update(&mut self, keyboard: &ButtonInput<KeyCode>, key_bindings: &KeyBindings) {
+        for key in GameKey::ALL {
+            let key_code = key_bindings.key_code(key);
+            self.pressed.set(key, keyboard.pressed(key_code));
+            if keyboard.just_pressed(key_code) {
+                self.just_pressed.set(key, true);
+            }
+            if keyboard.just_released(key_code) {
+                self.just_released.set(key, true);
+            }
+        }
+    }
+
+    /// Clear presses and releases, once a game step has seen them
+    pub fn consume(&mut self) {
+        self.just_pressed = GameKeys::default();
+        self.just_released = GameKeys::default();
+    }
+}
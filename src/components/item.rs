@@ -1,3 +1,4 @@
+use crate::components::{ability::Ability, animation::SpriteClips};
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
@@ -8,15 +9,36 @@ pub enum Item {
     Gem,
     Boots,
     Key,
+    Dagger,
+    Wings,
+    Cape,
+    Gauntlets,
     Unknown,
 }
 
+impl Item {
+    /// The [Ability] granted to the owner of this item
+    pub fn ability(&self) -> Option<Ability> {
+        match self {
+            Item::Boots => Some(Ability::HighJump),
+            Item::Wings => Some(Ability::DoubleJump),
+            Item::Cape => Some(Ability::Dash),
+            Item::Gauntlets => Some(Ability::WallJump),
+            _ => None,
+        }
+    }
+}
+
 impl From<&String> for Item {
     fn from(value: &String) -> Self {
         match value.as_str() {
             "Gem" => Item::Gem,
             "Boots" => Item::Boots,
             "Key" => Item::Key,
+            "Dagger" => Item::Dagger,
+            "Wings" => Item::Wings,
+            "Cape" => Item::Cape,
+            "Gauntlets" => Item::Gauntlets,
             _ => {
                 error!("Unknown item {value}");
                 Item::Unknown
@@ -37,10 +59,26 @@ impl Items {
         self.0.push(item);
     }
 
+    /// Remove one `item`, returning `false` if there was none
+    pub fn remove(&mut self, item: Item) -> bool {
+        match self.0.iter().position(|&i| i == item) {
+            Some(idx) => {
+                self.0.remove(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn contains(&self, item: Item) -> bool {
         self.0.contains(&item)
     }
 
+    /// Check if one of the items grants the `ability`
+    pub fn has_ability(&self, ability: Ability) -> bool {
+        self.0.iter().any(|item| item.ability() == Some(ability))
+    }
+
     pub fn contains_items(&self, items: &Items) -> bool {
         for item in items.0.iter() {
             if !self.0.contains(item) {
@@ -50,8 +88,9 @@ impl Items {
         true
     }
 
-    pub fn remove_items(&mut self, items: &Items) {
-        for item in items.0.iter() {
+    /// Remove the `items` used to open something, keeping the ones granting an [Ability]
+    pub fn use_items(&mut self, items: &Items) {
+        for item in items.0.iter().filter(|item| item.ability().is_none()) {
             if let Some(idx) = self.0.iter().position(|i| item == i) {
                 self.0.remove(idx);
             }
@@ -76,6 +115,17 @@ impl From<&EntityInstance> for Items {
 #[require(Name::new("Chest"), Items, RigidBody::Fixed, Collider::cuboid(8., 8.))]
 pub struct Chest;
 
+/// A [Chest] that was opened, until its opening animation is over
+#[derive(Component, Copy, Clone, Debug, Default)]
+pub struct OpenedChest;
+
+/// Clip of the [Chest] animation played once when it is opened
+pub const CHEST_OPEN_CLIP: &str = "open";
+
+/// Triggered on a [Chest] when the player opens it, with the items it contained
+#[derive(Event)]
+pub struct ChestOpenedEvent(pub Items);
+
 #[derive(Clone, Bundle, Default, LdtkEntity)]
 pub struct LdtkChestBundle {
     tag: Chest,
@@ -89,6 +139,8 @@ pub struct LdtkChestBundle {
 pub struct ItemAssets {
     pub texture: Handle<Image>,
     pub texture_atlas_layout: Handle<TextureAtlasLayout>,
+    #[dependency]
+    pub chest_animations: Handle<SpriteClips>,
 }
 
 impl FromWorld for ItemAssets {
@@ -102,24 +154,37 @@ impl FromWorld for ItemAssets {
                 None,
                 None,
             )),
+            chest_animations: world.load_asset("animations/chest.clips.ron"),
         }
     }
 }
 
 impl ItemAssets {
-    pub fn image_node(&self, item: Item) -> ImageNode {
+    fn texture_atlas(&self, item: Item) -> TextureAtlas {
         let index = match item {
             Item::Boots => 18,
             Item::Key => 83,
             Item::Gem => 1483,
+            Item::Dagger => 909,
+            Item::Wings => 16,
+            Item::Cape => 65,
+            Item::Gauntlets => 33,
             Item::Unknown => 0,
         };
-        ImageNode::from_atlas_image(
-            self.texture.clone(),
-            TextureAtlas {
-                layout: self.texture_atlas_layout.clone(),
-                index,
-            },
-        )
+        TextureAtlas {
+            layout: self.texture_atlas_layout.clone(),
+            index,
+        }
+    }
+
+    pub fn image_node(&self, item: Item) -> ImageNode {
+        ImageNode::from_atlas_image(self.texture.clone(), self.texture_atlas(item))
+    }
+
+    pub fn sprite(&self, item: Item, size: Vec2) -> Sprite {
+        Sprite {
+            custom_size: Some(size),
+            ..Sprite::from_atlas_image(self.texture.clone(), self.texture_atlas(item))
+        }
     }
 }
@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+/// A strong moment of the game, felt with a camera shake and a hit-stop
+#[derive(Event, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ImpactEvent {
+    PlayerHurt,
+    EnemyKilled,
+    HeavyLanding,
+    DoorSlam,
+}
+
+impl ImpactEvent {
+    /// [CameraShake] trauma added by the impact
+    pub fn trauma(&self) -> f32 {
+        match self {
+            ImpactEvent::PlayerHurt => 0.6,
+            ImpactEvent::EnemyKilled => 0.3,
+            ImpactEvent::HeavyLanding => 0.4,
+            ImpactEvent::DoorSlam => 0.25,
+        }
+    }
+
+    /// Duration of the [HitStop], in real seconds
+    pub fn hit_stop_secs(&self) -> f32 {
+        match self {
+            ImpactEvent::PlayerHurt => 0.12,
+            ImpactEvent::EnemyKilled => 0.08,
+            ImpactEvent::HeavyLanding => 0.05,
+            ImpactEvent::DoorSlam => 0.,
+        }
+    }
+}
+
+/// Shake of a camera, growing with its trauma which fades over time.
+///
+/// The shake is an offset added to the camera after it moved, and removed before
+/// it moves again, so it works whatever moves the camera.
+#[derive(Component, Default, Debug, Reflect)]
+#[reflect(Component)]
+pub struct CameraShake {
+    /// From 0 to 1
+    pub trauma: f32,
+    /// Offset currently added to the camera translation
+    pub offset: Vec2,
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).min(1.);
+    }
+}
+
+/// Accessibility setting to disable the [CameraShake]
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct ShakeSettings {
+    pub enabled: bool,
+}
+
+impl Default for ShakeSettings {
+    fn default() -> Self {
+        ShakeSettings { enabled: true }
+    }
+}
+
+/// The game time is slowed down for a short real time after an [ImpactEvent]
+#[derive(Resource, Default, Debug)]
+pub struct HitStop {
+    pub remaining_secs: f32,
+}
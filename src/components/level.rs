@@ -1,14 +1,16 @@
-use crate::components::item::Items;
+use crate::{components::item::Items, utils::grid_rectangles::grid_rectangles};
 use bevy::{
     ecs::query::{QueryData, QueryFilter},
     prelude::*,
 };
 use bevy_ecs_ldtk::{
     assets::{InternalLevels, LdtkJsonWithMetadata},
-    ldtk::loaded_level::LoadedLevel,
+    ldtk::{loaded_level::LoadedLevel, Level},
     prelude::*,
+    utils::ldtk_grid_coords_to_translation,
 };
 use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
 pub const _ENTITIES_LAYER: usize = 0;
@@ -16,6 +18,16 @@ pub const _WALL_SHADOWS_LAYER: usize = 1;
 pub const COLLISIONS_LAYER: usize = 2;
 pub const _BG_TEXTURES_LAYER: usize = 3;
 
+/// Path of the LDtk project of the game, in the assets
+#[derive(Resource, Clone, Debug)]
+pub struct LdtkProjectPath(pub String);
+
+impl Default for LdtkProjectPath {
+    fn default() -> Self {
+        LdtkProjectPath("load-runner.ldtk".into())
+    }
+}
+
 pub const DIRT_INT_CELL: i32 = 1;
 pub const LADDER_INT_CELL: i32 = 2;
 pub const STONE_INT_CELL: i32 = 3;
@@ -134,79 +146,27 @@ impl LevelColliders {
         }
     }
 
-    /// The algorithm used here is a nice compromise between simplicity, speed,
-    /// and a small number of rectangle colliders.
-    /// In basic terms, it will:
-    /// 1. consider where the walls are
-    /// 2. combine wall tiles into flat "plates" in each individual row
-    /// 3. combine the plates into rectangles across multiple rows wherever possible
-    /// It returns all rectangle corresponding to levels colliders
+    /// It returns all rectangle corresponding to levels colliders,
+    /// see [grid_rectangles]
     pub fn rectangles(&self, level: &Entity, width: i32, height: i32) -> Vec<IRect> {
-        let mut colliders = vec![];
-        if let Some(level_colliders) = self.level_to_colliders_locations.get(level) {
-            // combine wall tiles into flat "plates" in each individual row
-            let mut plate_stack: Vec<Vec<Plate>> = Vec::new();
-
-            for y in 0..height {
-                let mut row_plates: Vec<Plate> = Vec::new();
-                let mut plate_start = None;
-
-                // + 1 to the width so the algorithm "terminates" plates that touch the right edge
-                for x in 0..width + 1 {
-                    match (plate_start, level_colliders.contains(&GridCoords { x, y })) {
-                        (Some(s), false) => {
-                            row_plates.push(Plate {
-                                left: s,
-                                right: x - 1,
-                            });
-                            plate_start = None;
-                        }
-                        (None, true) => plate_start = Some(x),
-                        _ => (),
-                    }
-                }
-                plate_stack.push(row_plates);
-            }
-
-            // combine "plates" into rectangles across multiple rows
-            let mut rect_builder: HashMap<Plate, IRect> = HashMap::new();
-            let mut prev_row: Vec<Plate> = Vec::new();
-
-            // an extra empty row so the algorithm "finishes" the rects that touch the top edge
-            plate_stack.push(Vec::new());
-
-            for (y, current_row) in plate_stack.into_iter().enumerate() {
-                for prev_plate in &prev_row {
-                    if !current_row.contains(prev_plate) {
-                        // remove the finished rect so that the same plate in the future starts a new rect
-                        if let Some(rect) = rect_builder.remove(prev_plate) {
-                            // colliders.push(WallColliderBundle::new(rect, grid_size));
-                            colliders.push(rect);
-                        }
-                    }
-                }
-                for plate in &current_row {
-                    rect_builder
-                        .entry(plate.clone())
-                        .and_modify(|rect| rect.max.y += 1)
-                        .or_insert(IRect::new(plate.left, y as i32, plate.right, y as i32));
-                }
-                prev_row = current_row;
-            }
-        }
-        colliders
+        self.level_to_colliders_locations
+            .get(level)
+            .map(|cells| grid_rectangles(cells, width, height))
+            .unwrap_or_default()
     }
 }
 
+/// A collider of a level, merging its [ColliderCell]s in a rectangle of cells
+/// (bounds included)
 #[derive(Component)]
-pub struct LevelCollider;
+pub struct LevelCollider(pub IRect);
 
 pub fn level_collider(rect: IRect, grid_size: i32) -> impl Bundle {
     let scale = grid_size as f32 / 2.;
     let half_size = (rect.size() + ivec2(1, 1)).as_vec2() * scale;
     let pos = ivec2(rect.min.x + rect.max.x + 1, rect.min.y + rect.max.y + 1).as_vec2() * scale;
     (
-        LevelCollider,
+        LevelCollider(rect),
         Name::new("WallCollider"),
         Transform::from_translation(pos.extend(0.)),
         Collider::cuboid(half_size.x, half_size.y),
@@ -215,14 +175,6 @@ pub fn level_collider(rect: IRect, grid_size: i32) -> impl Bundle {
     )
 }
 
-/// Represents a wide wall that is 1 tile tall
-/// Used to spawn wall collisions
-#[derive(Clone, Eq, PartialEq, Debug, Default, Hash)]
-struct Plate {
-    left: i32,
-    right: i32,
-}
-
 #[derive(QueryData)]
 pub struct LevelData {
     pub entity: Entity,
@@ -242,6 +194,16 @@ impl<'w> LevelDataItem<'w> {
     }
 }
 
+/// Where the player starts a level, from its LDtk field `start`, relative to the level
+pub fn level_start(level: &Level, grid_size: i32) -> Option<Vec2> {
+    let start = level.get_point_field("start").ok()?;
+    Some(ldtk_grid_coords_to_translation(
+        *start,
+        level.px_hei / grid_size,
+        IVec2::splat(grid_size),
+    ))
+}
+
 pub trait LoadedLevelExt<'a> {
     fn collision_layer(&'a self) -> Result<&'a LayerInstance, BevyError>;
 }
@@ -254,5 +216,56 @@ impl<'a> LoadedLevelExt<'a> for LoadedLevel<'a> {
     }
 }
 
+/// How the camera moves to a neighbour level
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, Reflect)]
+pub enum LevelTransitionKind {
+    /// The camera slides to the new level
+    #[default]
+    Slide,
+    /// The screen fades to black, and back in the new level
+    Fade,
+}
+
+impl LevelTransitionKind {
+    pub const ALL: [LevelTransitionKind; 2] =
+        [LevelTransitionKind::Slide, LevelTransitionKind::Fade];
+}
+
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct LevelTransitionSettings {
+    pub kind: LevelTransitionKind,
+    pub duration_secs: f32,
+}
+
+impl Default for LevelTransitionSettings {
+    fn default() -> Self {
+        LevelTransitionSettings {
+            kind: LevelTransitionKind::Slide,
+            duration_secs: 0.6,
+        }
+    }
+}
+
+/// The transition to a neighbour level in progress, while the game is frozen
+#[derive(Resource, Debug)]
+pub struct LevelTransition {
+    pub kind: LevelTransitionKind,
+    pub timer: Timer,
+}
+
+impl LevelTransition {
+    pub fn new(settings: &LevelTransitionSettings) -> Self {
+        LevelTransition {
+            kind: settings.kind,
+            timer: Timer::from_seconds(settings.duration_secs, TimerMode::Once),
+        }
+    }
+}
+
+/// Event triggered on a level entity when some of its [ColliderCell]s changed,
+/// to update the [LevelCollider]s around them
 #[derive(Event)]
-pub struct UpdateCollidersEvent;
+pub struct UpdateCollidersEvent {
+    pub cells: Vec<GridCoords>,
+}
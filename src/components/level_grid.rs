@@ -0,0 +1,172 @@
+use crate::components::level::{
+    Destroyed, LdtkDirtCell, LdtkLadderCell, LdtkStoneCell, LdtkWaterCell, DIRT_INT_CELL,
+    LADDER_INT_CELL, STONE_INT_CELL, WATER_INT_CELL,
+};
+use bevy::{ecs::query::QueryData, prelude::*};
+use bevy_ecs_ldtk::prelude::*;
+use std::collections::HashMap;
+
+/// Kind of an IntGrid cell of a level
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Reflect)]
+pub enum CellKind {
+    Dirt,
+    Stone,
+    Ladder,
+    Water,
+}
+
+impl CellKind {
+    pub fn is_destructible(&self) -> bool {
+        *self == CellKind::Dirt
+    }
+
+    /// Kind of a value of the collision IntGrid layer
+    pub fn from_int_cell(value: i32) -> Option<Self> {
+        match value {
+            DIRT_INT_CELL => Some(CellKind::Dirt),
+            STONE_INT_CELL => Some(CellKind::Stone),
+            LADDER_INT_CELL => Some(CellKind::Ladder),
+            WATER_INT_CELL => Some(CellKind::Water),
+            _ => None,
+        }
+    }
+
+    /// Colour of the cell on maps
+    pub fn map_color(&self) -> Color {
+        match self {
+            CellKind::Dirt => Color::srgb(0.55, 0.4, 0.25),
+            CellKind::Stone => Color::srgb(0.6, 0.6, 0.6),
+            CellKind::Ladder => Color::srgb(0.85, 0.75, 0.3),
+            CellKind::Water => Color::srgb(0.25, 0.45, 0.9),
+        }
+    }
+}
+
+/// A cell of a [LevelGrid]
+#[derive(Clone, Copy, Debug, Reflect)]
+pub struct GridCell {
+    pub kind: CellKind,
+    pub entity: Entity,
+    /// A destructible cell was dug
+    pub destroyed: bool,
+}
+
+impl GridCell {
+    /// The cell is a wall, that can't be crossed
+    pub fn is_solid(&self) -> bool {
+        matches!(self.kind, CellKind::Dirt | CellKind::Stone) && !self.destroyed
+    }
+}
+
+#[derive(Default, Debug, Reflect)]
+struct LevelCells {
+    iid: String,
+    cells: HashMap<GridCoords, GridCell>,
+}
+
+/// Spatial index of the IntGrid cells of the spawned levels, to find a cell
+/// from its coordinates without iterating all of them.
+///
+/// Levels are identified by their entity.
+#[derive(Resource, Default, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct LevelGrid {
+    levels: HashMap<Entity, LevelCells>,
+}
+
+impl LevelGrid {
+    /// Index the `cells` of a spawned level, replacing its previous index if the level is respawned
+    pub fn insert_level(
+        &mut self,
+        level: Entity,
+        iid: &LevelIid,
+        cells: impl IntoIterator<Item = (GridCoords, GridCell)>,
+    ) {
+        self.remove_level(iid);
+        self.levels.insert(
+            level,
+            LevelCells {
+                iid: iid.to_string(),
+                cells: cells.into_iter().collect(),
+            },
+        );
+    }
+
+    pub fn remove_level(&mut self, iid: &LevelIid) {
+        let iid = iid.to_string();
+        self.levels.retain(|_, level| level.iid != iid);
+    }
+
+    pub fn clear(&mut self) {
+        self.levels.clear();
+    }
+
+    /// All the cells of a level
+    pub fn cells(&self, level: Entity) -> impl Iterator<Item = (&GridCoords, &GridCell)> {
+        self.levels
+            .get(&level)
+            .into_iter()
+            .flat_map(|level| level.cells.iter())
+    }
+
+    pub fn cell_at(&self, level: Entity, coords: GridCoords) -> Option<&GridCell> {
+        self.levels.get(&level)?.cells.get(&coords)
+    }
+
+    /// The cells left, right, above and below `coords`
+    pub fn neighbors(
+        &self,
+        level: Entity,
+        coords: GridCoords,
+    ) -> impl Iterator<Item = (GridCoords, &GridCell)> {
+        [(-1, 0), (1, 0), (0, 1), (0, -1)]
+            .into_iter()
+            .map(move |(dx, dy)| GridCoords::new(coords.x + dx, coords.y + dy))
+            .filter_map(move |coords| Some((coords, self.cell_at(level, coords)?)))
+    }
+
+    /// Mark a destructible cell as dug, or refilled
+    pub fn set_destroyed(&mut self, level: Entity, coords: GridCoords, destroyed: bool) {
+        if let Some(cell) = self
+            .levels
+            .get_mut(&level)
+            .and_then(|level| level.cells.get_mut(&coords))
+        {
+            cell.destroyed = destroyed;
+        }
+    }
+}
+
+/// IntGrid cells to index in the [LevelGrid]
+#[derive(QueryData)]
+pub struct GridCellData {
+    pub entity: Entity,
+    pub coords: &'static GridCoords,
+    pub layer: &'static ChildOf,
+    dirt: Has<LdtkDirtCell>,
+    stone: Has<LdtkStoneCell>,
+    ladder: Has<LdtkLadderCell>,
+    water: Has<LdtkWaterCell>,
+    destroyed: Has<Destroyed>,
+}
+
+impl GridCellDataItem<'_> {
+    pub fn cell(&self) -> Option<GridCell> {
+        let kind = if self.dirt {
+            CellKind::Dirt
+        } else if self.stone {
+            CellKind::Stone
+        } else if self.ladder {
+            CellKind::Ladder
+        } else if self.water {
+            CellKind::Water
+        } else {
+            return None;
+        };
+        Some(GridCell {
+            kind,
+            entity: self.entity,
+            destroyed: self.destroyed,
+        })
+    }
+}
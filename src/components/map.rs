@@ -0,0 +1,61 @@
+use crate::components::{level::COLLISIONS_LAYER, level_grid::CellKind};
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy_ecs_ldtk::ldtk::Level;
+use std::collections::HashSet;
+
+/// Iids of the levels the player has been in, the others are hidden by fog on the world map
+#[derive(Resource, Default, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct VisitedLevels(pub HashSet<String>);
+
+/// Show the full-screen map of the current world, over the pause menu
+#[derive(Event, Debug)]
+pub struct ShowWorldMapEvent;
+
+/// A transparent image of `size` pixels, with the `pixels` coloured.
+///
+/// Pixels are given from the top left corner, the ones outside of the image are ignored.
+pub fn map_image(size: UVec2, pixels: impl IntoIterator<Item = (IVec2, Color)>) -> Result<Image> {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    for (position, color) in pixels {
+        if let (Ok(x), Ok(y)) = (u32::try_from(position.x), u32::try_from(position.y)) {
+            if x < size.x && y < size.y {
+                image.set_color_at(x, y, color)?;
+            }
+        }
+    }
+    Ok(image)
+}
+
+/// Image of the collision cells of a level, as saved in the LDtk project
+pub fn level_thumbnail(level: &Level) -> Result<Image> {
+    let layer = level
+        .layer_instances
+        .as_ref()
+        .and_then(|layers| layers.get(COLLISIONS_LAYER))
+        .ok_or("Missing Collision")?;
+    let width = layer.c_wid.max(1);
+    let pixels = layer
+        .int_grid_csv
+        .iter()
+        .enumerate()
+        .filter_map(|(index, &value)| {
+            let position = IVec2::new(index as i32 % width, index as i32 / width);
+            Some((position, CellKind::from_int_cell(value)?.map_color()))
+        });
+    map_image(UVec2::new(layer.c_wid as u32, layer.c_hei as u32), pixels)
+}
@@ -1,10 +1,24 @@
+pub mod ability;
+pub mod animation;
+pub mod attack;
 pub mod character;
 pub mod enemy;
+pub mod ghost;
+pub mod high_score;
+pub mod input;
 pub mod item;
+pub mod juice;
 pub mod level;
+pub mod level_grid;
+pub mod map;
+pub mod particles;
 pub mod player;
+pub mod progress;
+pub mod replay;
+pub mod score;
+pub mod speedrun;
 
-pub use utils::{despawn_all, GROUP_ENEMY, GROUP_PLAYER};
+pub use utils::{despawn_all, GROUP_ENEMY, GROUP_PLAYER, GROUP_PLAYER_ATTACK};
 
 mod utils {
     use bevy::prelude::*;
@@ -12,6 +26,7 @@ mod utils {
 
     pub const GROUP_PLAYER: Group = Group::GROUP_1;
     pub const GROUP_ENEMY: Group = Group::GROUP_2;
+    pub const GROUP_PLAYER_ATTACK: Group = Group::GROUP_3;
 
     /// Generic system that takes a component as a parameter, and will despawn all entities with that component
     pub fn despawn_all<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {
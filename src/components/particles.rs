@@ -0,0 +1,145 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// How the particles of an effect are emitted and evolve
+#[derive(Clone, Debug, Deserialize)]
+pub struct ParticlePreset {
+    /// Number of particles of a burst
+    pub count: u32,
+    /// Minimum and maximum speeds, in pixels per second
+    pub speed: (f32, f32),
+    /// Mean direction of the particles, in degrees, 90 is up
+    #[serde(default = "default_direction")]
+    pub direction: f32,
+    /// Angle, in degrees, over which the particles are spread around the direction
+    #[serde(default = "default_spread")]
+    pub spread: f32,
+    /// Downward acceleration, in pixels per second squared
+    #[serde(default)]
+    pub gravity: f32,
+    /// Seconds before a particle disappears
+    pub lifetime: f32,
+    /// Side of the square particles, in pixels
+    #[serde(default = "default_size")]
+    pub size: f32,
+    /// Colour of a new particle, as linear RGBA
+    pub start_color: (f32, f32, f32, f32),
+    /// Colour of a particle at the end of its lifetime
+    pub end_color: (f32, f32, f32, f32),
+}
+
+fn default_direction() -> f32 {
+    90.
+}
+
+fn default_spread() -> f32 {
+    360.
+}
+
+fn default_size() -> f32 {
+    2.
+}
+
+/// Particle effects by name, loaded from a `.particles.ron` file:
+///
+/// ```ron
+/// {
+///     "dig": (count: 12, speed: (40, 90), spread: 120, gravity: 300, lifetime: 0.6,
+///             start_color: (0.5, 0.35, 0.2, 1), end_color: (0.5, 0.35, 0.2, 0)),
+/// }
+/// ```
+#[derive(Asset, TypePath, Debug)]
+pub struct ParticlePresets {
+    presets: HashMap<String, ParticlePreset>,
+}
+
+impl ParticlePresets {
+    pub fn get(&self, preset: &str) -> Option<&ParticlePreset> {
+        self.presets.get(preset)
+    }
+}
+
+#[derive(Default)]
+pub struct ParticlePresetsLoader;
+
+impl AssetLoader for ParticlePresetsLoader {
+    type Asset = ParticlePresets;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<ParticlePresets, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let presets = ron::de::from_bytes::<HashMap<String, ParticlePreset>>(&bytes)?;
+        for (name, preset) in &presets {
+            if preset.lifetime <= 0. {
+                return Err(format!("Particles {name}: lifetime should be positive").into());
+            }
+            if preset.speed.0 > preset.speed.1 {
+                return Err(format!("Particles {name}: speed should be (min, max)").into());
+            }
+        }
+        Ok(ParticlePresets { presets })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["particles.ron"]
+    }
+}
+
+/// All particle assets
+#[derive(Resource, Clone, Asset, TypePath)]
+pub struct ParticleAssets {
+    #[dependency]
+    pub presets: Handle<ParticlePresets>,
+}
+
+impl FromWorld for ParticleAssets {
+    fn from_world(world: &mut World) -> Self {
+        ParticleAssets {
+            presets: world.load_asset("effects.particles.ron"),
+        }
+    }
+}
+
+/// Emit a burst of particles of a [ParticlePreset]
+#[derive(Event, Debug)]
+pub struct ParticleEmitEvent {
+    pub preset: &'static str,
+    pub position: Vec2,
+}
+
+impl ParticleEmitEvent {
+    pub fn new(preset: &'static str, position: Vec2) -> Self {
+        ParticleEmitEvent { preset, position }
+    }
+}
+
+/// A sprite moving on its own until it expires, fading from a colour to another
+#[derive(Component, Debug)]
+#[require(Name::new("Particle"), Sprite)]
+pub struct Particle {
+    pub velocity: Vec2,
+    pub gravity: f32,
+    /// Seconds since the particle was emitted
+    pub age: f32,
+    pub lifetime: f32,
+    pub start_color: LinearRgba,
+    pub end_color: LinearRgba,
+}
+
+impl Particle {
+    pub fn color(&self) -> Color {
+        let fraction = (self.age / self.lifetime).min(1.);
+        self.start_color.mix(&self.end_color, fraction).into()
+    }
+}
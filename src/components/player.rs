@@ -1,6 +1,10 @@
 use crate::components::{
+    ability::{AirJump, DashCooldown},
+    animation::SpriteClips,
+    attack::AttackCooldown,
     character::{
-        AnimationTimer, Climber, GroundDetection, InWater, JumpSpeed, Jumping, Life, Speed,
+        Climber, Facing, FallDamage, FallTracker, GroundDetection, InWater, JumpControl, JumpSpeed,
+        JumpTimings, Jumping, Life, Speed, WallDetection,
     },
     item::Items,
     GROUP_PLAYER,
@@ -12,45 +16,21 @@ use bevy_rapier2d::prelude::*;
 /// All [Player] assets
 #[derive(Resource, Clone, Asset, TypePath)]
 pub struct PlayerAssets {
-    pub walk_sprites: Handle<Image>,
-    pub walk_atlas_layout: Handle<TextureAtlasLayout>,
-    pub jump_sprites: Handle<Image>,
-    pub jump_atlas_layout: Handle<TextureAtlasLayout>,
-    pub death_sprites: Handle<Image>,
-    pub death_atlas_layout: Handle<TextureAtlasLayout>,
+    #[dependency]
+    pub animations: Handle<SpriteClips>,
 }
 
 impl FromWorld for PlayerAssets {
     fn from_world(world: &mut World) -> Self {
         PlayerAssets {
-            walk_sprites: world.load_asset("player/walk.png"),
-            walk_atlas_layout: world.add_asset(TextureAtlasLayout::from_grid(
-                UVec2::splat(16),
-                8,
-                4,
-                Some(UVec2::splat(64)),
-                Some(UVec2::splat(32)),
-            )),
-            jump_sprites: world.load_asset("player/jump.png"),
-            jump_atlas_layout: world.add_asset(TextureAtlasLayout::from_grid(
-                UVec2::splat(16),
-                6,
-                4,
-                Some(UVec2::splat(64)),
-                Some(UVec2::splat(32)),
-            )),
-            death_sprites: world.load_asset("player/death.png"),
-            death_atlas_layout: world.add_asset(TextureAtlasLayout::from_grid(
-                UVec2::splat(16),
-                6,
-                4,
-                Some(UVec2::splat(64)),
-                Some(UVec2::splat(32)),
-            )),
+            animations: world.load_asset("animations/player.clips.ron"),
         }
     }
 }
 
+/// Half size of the [Player] collider
+pub const PLAYER_HALF_SIZE: Vec2 = Vec2::new(5., 7.);
+
 /// The player component
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
 #[require(
@@ -59,16 +39,24 @@ impl FromWorld for PlayerAssets {
     Life,
     Speed,
     JumpSpeed,
+    JumpControl,
+    JumpTimings,
+    FallDamage,
+    FallTracker,
     Items,
+    AttackCooldown,
+    AirJump,
+    DashCooldown,
     // Markers
     Climber,
     GroundDetection,
+    WallDetection,
     Jumping,
     InWater,
+    Facing,
     // Sprite
     Sprite,
-    AnimationTimer,
-    // Physics
+    // Physics, see PLAYER_HALF_SIZE
     Collider::round_cuboid(3., 5., 2.),
     RigidBody::Dynamic,
     Velocity,
@@ -94,6 +82,10 @@ pub struct LdtkPlayerBundle {
     speed: Speed,
     #[from_entity_instance]
     jump_speed: JumpSpeed,
+    #[from_entity_instance]
+    jump_control: JumpControl,
+    #[from_entity_instance]
+    fall_damage: FallDamage,
     #[sprite("player/walk.png")]
     sprite: Sprite,
     #[worldly]
@@ -104,8 +96,8 @@ pub struct LdtkPlayerBundle {
     entity_instance: EntityInstance,
 }
 
-#[derive(Event)]
-pub struct PlayerDeathEvent;
+/// Clip of the [Player] animation played once when it dies
+pub const PLAYER_DEATH_CLIP: &str = "death";
 
 #[derive(Event)]
 pub struct DigEvent;
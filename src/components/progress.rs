@@ -0,0 +1,88 @@
+use crate::storage;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+const PROGRESS_FILE: &str = "progress.ron";
+
+/// Best results of a completed level
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Reflect)]
+pub struct LevelRecord {
+    /// Fastest completion, in seconds
+    pub best_secs: f32,
+    pub best_score: u32,
+}
+
+/// Levels completed by the player, saved between game sessions
+#[derive(Resource, Clone, Default, Debug, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Progress {
+    /// Records by level identifier
+    pub levels: BTreeMap<String, LevelRecord>,
+    /// Identifiers of the levels the player has been in
+    pub reached: BTreeSet<String>,
+}
+
+impl Progress {
+    pub fn load() -> Self {
+        storage::load(PROGRESS_FILE)
+    }
+
+    pub fn save(&self) -> Result {
+        storage::save(PROGRESS_FILE, self)
+    }
+
+    pub fn record(&self, level: &str) -> Option<&LevelRecord> {
+        self.levels.get(level)
+    }
+
+    /// Remember the player has been in a level, returns `true` the first time
+    pub fn reach(&mut self, level: &str) -> bool {
+        self.reached.insert(level.to_string())
+    }
+
+    /// Record the completion of a level, keeping the best time and score
+    pub fn complete(&mut self, level: &str, secs: f32, score: u32) {
+        let record = self.levels.entry(level.to_string()).or_insert(LevelRecord {
+            best_secs: secs,
+            best_score: score,
+        });
+        record.best_secs = record.best_secs.min(secs);
+        record.best_score = record.best_score.max(score);
+    }
+}
+
+/// Whether a level can be chosen in the level select menu
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LevelLock {
+    Locked,
+    Unlocked,
+    Completed,
+}
+
+impl LevelLock {
+    /// The first level of a world is always unlocked, the next ones once the previous one is
+    /// completed or once the player has been in them
+    pub fn of(progress: &Progress, level: &str, previous_level: Option<&str>) -> Self {
+        if progress.record(level).is_some() {
+            LevelLock::Completed
+        } else if previous_level.is_none_or(|previous| progress.record(previous).is_some())
+            || progress.reached.contains(level)
+        {
+            LevelLock::Unlocked
+        } else {
+            LevelLock::Locked
+        }
+    }
+}
+
+/// The current run, started from a new game or the level select menu
+#[derive(Resource, Clone, Default, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct CurrentRun {
+    /// Identifier of the LDtk level where the run started, credited when it is completed
+    pub start_level: Option<String>,
+    /// The run started from the level select menu, it isn't a complete run of the game
+    pub from_level_select: bool,
+}
@@ -0,0 +1,147 @@
+use super::input::GameInput;
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// File of the last recorded game, in the user data directory
+pub const LAST_REPLAY_FILE: &str = "last_replay.ron";
+
+/// Level where a replay starts, see [LevelSelection]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StartLevel {
+    Identifier(String),
+    Indices { world: Option<usize>, level: usize },
+    Iid(String),
+    Uid(i32),
+}
+
+impl Default for StartLevel {
+    fn default() -> Self {
+        StartLevel::Uid(0)
+    }
+}
+
+impl From<&LevelSelection> for StartLevel {
+    fn from(level_selection: &LevelSelection) -> Self {
+        match level_selection {
+            LevelSelection::Identifier(identifier) => StartLevel::Identifier(identifier.clone()),
+            LevelSelection::Indices(indices) => StartLevel::Indices {
+                world: indices.world,
+                level: indices.level,
+            },
+            LevelSelection::Iid(iid) => StartLevel::Iid(iid.to_string()),
+            LevelSelection::Uid(uid) => StartLevel::Uid(*uid),
+        }
+    }
+}
+
+impl From<&StartLevel> for LevelSelection {
+    fn from(start_level: &StartLevel) -> Self {
+        match start_level {
+            StartLevel::Identifier(identifier) => LevelSelection::Identifier(identifier.clone()),
+            StartLevel::Indices { world, level } => LevelSelection::Indices(LevelIndices {
+                world: *world,
+                level: *level,
+            }),
+            StartLevel::Iid(iid) => LevelSelection::iid(iid.clone()),
+            StartLevel::Uid(uid) => LevelSelection::Uid(*uid),
+        }
+    }
+}
+
+/// How a replay ended
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReplayEnd {
+    LevelEnded,
+    PlayerDied,
+    /// The game was left before the end of the level
+    Interrupted,
+}
+
+/// State of the game at the end of a replay
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayOutcome {
+    pub end: ReplayEnd,
+    pub steps: u32,
+    pub player_translation: Option<[f32; 2]>,
+    pub player_life: Option<u16>,
+    pub player_items: Vec<String>,
+    pub points: u32,
+}
+
+/// Inputs of a game, from the start of a level
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub start_level: StartLevel,
+    /// Input of each game step, as runs of identical inputs
+    pub steps: Vec<(u32, GameInput)>,
+    /// Outcome of the recorded game, to check the replay gives the same result
+    pub outcome: Option<ReplayOutcome>,
+}
+
+impl Replay {
+    pub fn new(start_level: StartLevel) -> Self {
+        Replay {
+            start_level,
+            ..Default::default()
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        Ok(ron::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Number of game steps
+    pub fn step_count(&self) -> u32 {
+        self.steps.iter().map(|(count, _)| count).sum()
+    }
+
+    pub fn push(&mut self, input: GameInput) {
+        match self.steps.last_mut() {
+            Some((count, last_input)) if *last_input == input => *count += 1,
+            _ => self.steps.push((1, input)),
+        }
+    }
+}
+
+/// Game being recorded, if any
+#[derive(Resource, Default)]
+pub struct Recording(pub Option<Replay>);
+
+/// Replay being played, instead of reading the keyboard
+#[derive(Resource)]
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    /// Current run of [Replay::steps]
+    run: usize,
+    /// Current step in the run
+    run_step: u32,
+    /// Number of steps played
+    pub steps: u32,
+    pub finished: bool,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayer {
+            replay,
+            run: 0,
+            run_step: 0,
+            steps: 0,
+            finished: false,
+        }
+    }
+
+    /// Input of the next game step, if the replay is not over
+    pub fn next_input(&mut self) -> Option<GameInput> {
+        let &(count, input) = self.replay.steps.get(self.run)?;
+        self.run_step += 1;
+        if self.run_step >= count {
+            self.run += 1;
+            self.run_step = 0;
+        }
+        self.steps += 1;
+        Some(input)
+    }
+}
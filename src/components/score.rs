@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{ldtk::Level, prelude::*};
+
+pub const GEM_POINTS: u32 = 100;
+pub const ENEMY_POINTS: u32 = 200;
+pub const CHEST_POINTS: u32 = 50;
+pub const TIME_BONUS_POINTS_PER_SEC: f32 = 10.;
+
+/// What the player achieved in the current level
+#[derive(Resource, Clone, Copy, Default, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct Score {
+    pub gems: u32,
+    pub enemies: u32,
+    pub chests: u32,
+    /// Time spent running the level
+    pub elapsed_secs: f32,
+}
+
+impl Score {
+    pub fn gem_points(&self) -> u32 {
+        self.gems * GEM_POINTS
+    }
+
+    pub fn enemy_points(&self) -> u32 {
+        self.enemies * ENEMY_POINTS
+    }
+
+    pub fn chest_points(&self) -> u32 {
+        self.chests * CHEST_POINTS
+    }
+
+    /// Points earned, without the time bonus
+    pub fn points(&self) -> u32 {
+        self.gem_points() + self.enemy_points() + self.chest_points()
+    }
+
+    /// Bonus for the time left before the level time limit
+    pub fn time_bonus(&self, scoring: &LevelScoring) -> u32 {
+        ((scoring.time_limit_secs - self.elapsed_secs).max(0.) * TIME_BONUS_POINTS_PER_SEC) as u32
+    }
+
+    pub fn total(&self, scoring: &LevelScoring) -> u32 {
+        self.points() + self.time_bonus(scoring)
+    }
+}
+
+/// Time limit and star thresholds of a level
+#[derive(Clone, Copy, Debug)]
+pub struct LevelScoring {
+    pub time_limit_secs: f32,
+    pub two_stars: u32,
+    pub three_stars: u32,
+}
+
+const DEFAULT_TIME_LIMIT_SECS: f32 = 300.;
+const DEFAULT_TWO_STARS: u32 = 1000;
+const DEFAULT_THREE_STARS: u32 = 2000;
+
+impl Default for LevelScoring {
+    fn default() -> Self {
+        LevelScoring {
+            time_limit_secs: DEFAULT_TIME_LIMIT_SECS,
+            two_stars: DEFAULT_TWO_STARS,
+            three_stars: DEFAULT_THREE_STARS,
+        }
+    }
+}
+
+/// Fields of the level that are missing, or negative scores, use the defaults
+impl From<&Level> for LevelScoring {
+    fn from(level: &Level) -> Self {
+        LevelScoring {
+            time_limit_secs: level
+                .get_float_field("time_limit")
+                .copied()
+                .unwrap_or(DEFAULT_TIME_LIMIT_SECS),
+            two_stars: level
+                .get_int_field("two_stars_score")
+                .ok()
+                .and_then(|&score| u32::try_from(score).ok())
+                .unwrap_or(DEFAULT_TWO_STARS),
+            three_stars: level
+                .get_int_field("three_stars_score")
+                .ok()
+                .and_then(|&score| u32::try_from(score).ok())
+                .unwrap_or(DEFAULT_THREE_STARS),
+        }
+    }
+}
+
+impl LevelScoring {
+    /// Rating, from 1 to 3 stars
+    pub fn stars(&self, total: u32) -> u8 {
+        if total >= self.three_stars {
+            3
+        } else if total >= self.two_stars {
+            2
+        } else {
+            1
+        }
+    }
+}
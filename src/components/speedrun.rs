@@ -0,0 +1,122 @@
+use bevy::{prelude::*, time::Stopwatch};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub const PERSONAL_BESTS_FILE: &str = "personal_bests.ron";
+
+/// Time of the run when the player left a level
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect)]
+pub struct Split {
+    /// Identifier of the LDtk level
+    pub level: String,
+    /// Time of the run, in seconds
+    pub run_secs: f32,
+    /// Time spent in the level, in seconds
+    pub segment_secs: f32,
+}
+
+/// Timer of the current run
+///
+/// It is only ticked while the game is [running](crate::schedule::InGameState::Running),
+/// so it is paused with the game.
+#[derive(Resource, Default, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct Speedrun {
+    /// Time since the start of the run
+    pub run: Stopwatch,
+    /// Time since the player entered the current level
+    pub segment: Stopwatch,
+    /// Identifier of the current LDtk level
+    pub level: Option<String>,
+    pub splits: Vec<Split>,
+}
+
+impl Speedrun {
+    /// End the segment of the current level, and start a new one in `next_level`
+    pub fn split(&mut self, next_level: Option<String>) {
+        if let Some(level) = self.level.take() {
+            self.splits.push(Split {
+                level,
+                run_secs: self.run.elapsed_secs(),
+                segment_secs: self.segment.elapsed_secs(),
+            });
+        }
+        self.segment.reset();
+        self.level = next_level;
+    }
+}
+
+/// Best times, saved between game sessions
+#[derive(Resource, Clone, Default, Debug, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct PersonalBests {
+    /// Splits of the fastest complete run
+    pub run: Option<Vec<Split>>,
+    /// Fastest time spent in each level, in seconds
+    pub segments: HashMap<String, f32>,
+}
+
+impl PersonalBests {
+    pub fn run_secs(&self) -> Option<f32> {
+        self.run
+            .as_ref()
+            .and_then(|splits| splits.last())
+            .map(|split| split.run_secs)
+    }
+
+    /// Split of the best run to compare with the `index`-th split of the current run
+    pub fn compared_split(&self, index: usize, level: &str) -> Option<&Split> {
+        self.run
+            .as_ref()
+            .and_then(|splits| splits.get(index))
+            .filter(|split| split.level == level)
+    }
+
+    /// Keep the fastest time spent in each level of a run
+    pub fn record_segments(&mut self, splits: &[Split]) {
+        for split in splits {
+            let best = self
+                .segments
+                .entry(split.level.clone())
+                .or_insert(split.segment_secs);
+            *best = best.min(split.segment_secs);
+        }
+    }
+
+    /// Record the splits of a complete run, returns `true` if it is a new best run
+    pub fn record(&mut self, splits: &[Split]) -> bool {
+        self.record_segments(splits);
+        let Some(run_secs) = splits.last().map(|split| split.run_secs) else {
+            return false;
+        };
+        if self.run_secs().is_none_or(|best| run_secs < best) {
+            self.run = Some(splits.to_vec());
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Show the splits of the current run compared with the best run
+#[derive(Resource, Default, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct ShowSplits(pub bool);
+
+/// Format a time as `m:ss.cc`
+pub fn format_time(secs: f32) -> String {
+    let centis = (secs * 100.).round() as u32;
+    format!(
+        "{}:{:02}.{:02}",
+        centis / 6000,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
+
+/// Format the difference with a reference time as `+s.cc` or `-s.cc`
+pub fn format_delta(secs: f32, reference_secs: f32) -> String {
+    let delta = secs - reference_secs;
+    let sign = if delta < 0. { '-' } else { '+' };
+    format!("{sign}{:.2}", delta.abs())
+}
@@ -0,0 +1,123 @@
+use crate::{
+    components::{
+        ability::{Ability, AirJump, DashCooldown, Dashing, WallJumping, DASH_DURATION_SECS},
+        character::{Dying, Facing, FallTracker, GroundDetection, HitStun, WallDetection},
+        input::{GameInput, GameKey},
+        item::Items,
+        player::Player,
+        GROUP_ENEMY,
+    },
+    schedule::InGameSet,
+    utils::invulnerable::Invulnerable,
+};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use std::time::Duration;
+
+pub fn ability_plugin(app: &mut App) {
+    app.register_type::<Ability>()
+        .register_type::<AirJump>()
+        .add_systems(
+            FixedUpdate,
+            (tick_abilities, dash.after(tick_abilities)).in_set(InGameSet::UserInput),
+        )
+        .add_systems(
+            FixedUpdate,
+            (dashing, wall_slide).in_set(InGameSet::EntityUpdate),
+        );
+}
+
+const DASH_SPEED: f32 = 360.;
+const WALL_SLIDE_SPEED: f32 = 40.;
+
+fn tick_abilities(
+    mut commands: Commands,
+    mut dash_cooldowns: Query<&mut DashCooldown>,
+    mut dashings: Query<(Entity, &mut Dashing)>,
+    mut wall_jumpings: Query<(Entity, &mut WallJumping)>,
+    time: Res<Time>,
+) {
+    for mut cooldown in &mut dash_cooldowns {
+        cooldown.tick(time.delta());
+    }
+    for (entity, mut dashing) in &mut dashings {
+        if dashing.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Dashing>();
+        }
+    }
+    for (entity, mut wall_jumping) in &mut wall_jumpings {
+        if wall_jumping.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<WallJumping>();
+        }
+    }
+}
+
+fn dash(
+    mut commands: Commands,
+    input: Res<GameInput>,
+    mut players: Query<
+        (Entity, &Items, &mut DashCooldown, Has<Invulnerable>),
+        (
+            With<Player>,
+            Without<Dying>,
+            Without<HitStun>,
+            Without<Dashing>,
+        ),
+    >,
+) {
+    if !input.just_pressed(GameKey::Dash) {
+        return;
+    }
+    if let Ok((player_entity, items, mut cooldown, invulnerable)) = players.single_mut() {
+        if items.has_ability(Ability::Dash) && cooldown.finished() {
+            cooldown.reset();
+            let mut player = commands.entity(player_entity);
+            player.insert(Dashing::default());
+            // Don't shorten a longer invulnerability
+            if !invulnerable {
+                player.insert(Invulnerable::new(
+                    Duration::from_secs_f32(DASH_DURATION_SECS),
+                    GROUP_ENEMY,
+                ));
+            }
+        }
+    }
+}
+
+/// A dash is a straight horizontal move
+fn dashing(mut dashers: Query<(&Facing, &mut Velocity), With<Dashing>>) {
+    for (facing, mut velocity) in &mut dashers {
+        velocity.linvel = Vec2::new(facing.sign() * DASH_SPEED, 0.);
+    }
+}
+
+/// Falling along a wall is slowed down while pushing toward it, and is not a fall that hurts
+fn wall_slide(
+    input: Res<GameInput>,
+    mut players: Query<
+        (
+            &Items,
+            &Facing,
+            &GroundDetection,
+            &WallDetection,
+            &mut Velocity,
+            &mut FallTracker,
+        ),
+        (With<Player>, Without<Dashing>),
+    >,
+) {
+    let pushing = input.pressed(GameKey::Left) || input.pressed(GameKey::Right);
+    for (items, &facing, ground_detection, wall_detection, mut velocity, mut tracker) in
+        &mut players
+    {
+        if pushing
+            && !ground_detection.on_ground
+            && wall_detection.on_wall(facing)
+            && items.has_ability(Ability::WallJump)
+            && velocity.linvel.y < 0.
+        {
+            velocity.linvel.y = velocity.linvel.y.max(-WALL_SLIDE_SPEED);
+            tracker.peak_y = None;
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::{
+    components::animation::{ClipFinishedEvent, SpriteAnimation, SpriteClips, SpriteClipsLoader},
+    schedule::InGameSet,
+};
+use bevy::prelude::*;
+
+pub fn animation_plugin(app: &mut App) {
+    app.init_asset::<SpriteClips>()
+        .init_asset_loader::<SpriteClipsLoader>()
+        .register_type::<SpriteAnimation>()
+        .add_systems(FixedUpdate, animate_sprites.in_set(InGameSet::EntityUpdate));
+}
+
+/// Show the current frame of each [SpriteAnimation]
+pub fn animate_sprites(
+    mut commands: Commands,
+    mut animations: Query<(Entity, &mut SpriteAnimation, &mut Sprite)>,
+    sprite_clips: Res<Assets<SpriteClips>>,
+    time: Res<Time>,
+) {
+    for (entity, mut animation, mut sprite) in &mut animations {
+        let Some(clip) = sprite_clips
+            .get(&animation.clips)
+            .and_then(|clips| clips.get(animation.clip()))
+        else {
+            continue;
+        };
+        let (frame, just_finished) = animation.tick(clip, time.delta_secs());
+        let index = clip.frames[frame];
+
+        // Only change what differs, so the sprite isn't modified on each step
+        if sprite.image != clip.image {
+            sprite.image = clip.image.clone();
+        }
+        let shown = sprite
+            .texture_atlas
+            .as_ref()
+            .is_some_and(|atlas| atlas.layout == clip.layout && atlas.index == index);
+        if !shown {
+            sprite.texture_atlas = Some(TextureAtlas {
+                layout: clip.layout.clone(),
+                index,
+            });
+        }
+
+        if just_finished {
+            commands.trigger_targets(
+                ClipFinishedEvent {
+                    clip: animation.clip().into(),
+                },
+                entity,
+            );
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{
+    components::{
+        attack::{melee_hitbox, projectile, AttackCooldown, Attacking, MeleeHitbox, Projectile},
+        character::{Damage, Dying, Facing, HitStun, Life},
+        enemy::{Enemy, EnemyDeathEvent},
+        input::{GameInput, GameKey},
+        item::{Item, ItemAssets, Items},
+        player::Player,
+        GROUP_PLAYER_ATTACK,
+    },
+    schedule::InGameSet,
+    utils::{collisions::Collisions, invulnerable::Invulnerable},
+};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use std::time::Duration;
+
+pub fn attack_plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (tick_attack, (melee_attack, throw_item).after(tick_attack)).in_set(InGameSet::UserInput),
+    )
+    .add_systems(
+        FixedUpdate,
+        (melee_hits_enemy, projectile_hits).in_set(InGameSet::CollisionDetection),
+    );
+}
+
+const ENEMY_INVULNERABILITY_SECS: f32 = 0.5;
+
+fn tick_attack(
+    mut commands: Commands,
+    mut attackers: Query<(Entity, &mut AttackCooldown, Option<&mut Attacking>)>,
+    time: Res<Time>,
+) {
+    for (entity, mut cooldown, attacking) in &mut attackers {
+        cooldown.tick(time.delta());
+        if let Some(mut attacking) = attacking {
+            if attacking.tick(time.delta()).finished() {
+                commands.entity(entity).remove::<Attacking>();
+            }
+        }
+    }
+}
+
+fn melee_attack(
+    mut commands: Commands,
+    input: Res<GameInput>,
+    mut players: Query<
+        (Entity, &Facing, &mut AttackCooldown),
+        (With<Player>, Without<Dying>, Without<HitStun>),
+    >,
+) {
+    if !input.just_pressed(GameKey::Attack) {
+        return;
+    }
+    if let Ok((player_entity, &facing, mut cooldown)) = players.single_mut() {
+        if cooldown.finished() {
+            cooldown.reset();
+            commands.entity(player_entity).insert(Attacking::default());
+            commands.spawn(melee_hitbox(player_entity, facing));
+        }
+    }
+}
+
+fn throw_item(
+    mut commands: Commands,
+    input: Res<GameInput>,
+    mut players: Query<
+        (Entity, &Transform, &Facing, &mut AttackCooldown, &mut Items),
+        (With<Player>, Without<Dying>, Without<HitStun>),
+    >,
+    assets: Res<ItemAssets>,
+) {
+    if !input.just_pressed(GameKey::Throw) {
+        return;
+    }
+    if let Ok((player_entity, transform, &facing, mut cooldown, mut items)) = players.single_mut() {
+        if cooldown.finished() && items.remove(Item::Dagger) {
+            info!("Player throws {:?}", Item::Dagger);
+            cooldown.reset();
+            commands.entity(player_entity).insert(Attacking::default());
+            commands.spawn(projectile(
+                transform.translation,
+                facing,
+                assets.sprite(Item::Dagger, Vec2::splat(12.)),
+            ));
+        }
+    }
+}
+
+/// Hit an enemy, making it invulnerable to player attacks for a while
+fn hit_enemy(commands: &mut Commands, enemy_entity: Entity, life: &mut Life, damage: Damage) {
+    if life.is_dead() {
+        // Already killed by another hit this frame
+        return;
+    }
+    life.hit(damage.0);
+    if life.is_dead() {
+        commands.trigger_targets(EnemyDeathEvent, enemy_entity);
+    } else {
+        commands.entity(enemy_entity).insert(Invulnerable::new(
+            Duration::from_secs_f32(ENEMY_INVULNERABILITY_SECS),
+            GROUP_PLAYER_ATTACK,
+        ));
+    }
+}
+
+fn melee_hits_enemy(
+    mut commands: Commands,
+    mut collisions: Collisions<With<MeleeHitbox>, (With<Enemy>, Without<Invulnerable>)>,
+    hitboxes: Query<&Damage, With<MeleeHitbox>>,
+    mut enemies: Query<&mut Life, (With<Enemy>, Without<Invulnerable>)>,
+) {
+    for (hitbox_entity, enemy_entity) in collisions.started() {
+        if let (Ok(&damage), Ok(mut life)) =
+            (hitboxes.get(hitbox_entity), enemies.get_mut(enemy_entity))
+        {
+            hit_enemy(&mut commands, enemy_entity, &mut life, damage);
+        }
+    }
+}
+
+/// A [Projectile] is destroyed by the enemies and the solid colliders it hits, it goes
+/// through [Sensor]s like ladders and doors
+fn projectile_hits(
+    mut commands: Commands,
+    mut collisions: Collisions<With<Projectile>, Or<(With<Enemy>, Without<Sensor>)>>,
+    projectiles: Query<&Damage, With<Projectile>>,
+    mut enemies: Query<&mut Life, (With<Enemy>, Without<Invulnerable>)>,
+) {
+    for (projectile_entity, other_entity) in collisions.started() {
+        let Ok(&damage) = projectiles.get(projectile_entity) else {
+            continue;
+        };
+        if let Ok(mut life) = enemies.get_mut(other_entity) {
+            hit_enemy(&mut commands, other_entity, &mut life, damage);
+        }
+        commands.entity(projectile_entity).try_despawn();
+    }
+}
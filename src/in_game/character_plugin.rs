@@ -1,15 +1,24 @@
 use crate::{
     components::{
         character::{
-            Climber, GroundDetection, GroundSensor, InWater, JumpSpeed, Jumping, Life, Speed,
+            Climber, Dying, Facing, FallDamage, FallTracker, GroundDetection, GroundSensor,
+            HitStun, InWater, JumpControl, JumpSpeed, JumpTimings, Jumping, Life, Speed,
+            WallDetection, WallSensor,
         },
-        level::{Climbable, LdtkWaterCell, COLLISIONS_LAYER},
+        juice::ImpactEvent,
+        level::{Climbable, LevelCollider, COLLISIONS_LAYER},
+        level_grid::{CellKind, LevelGrid},
     },
     schedule::InGameSet,
+    utils::{
+        collisions::{CollisionPair, Collisions},
+        invulnerable::Invulnerable,
+    },
 };
 use bevy::prelude::*;
 use bevy_ecs_ldtk::{prelude::*, utils::translation_to_grid_coords};
 use bevy_rapier2d::prelude::*;
+use std::time::Duration;
 
 pub fn character_plugin(app: &mut App) {
     app.register_type::<Life>()
@@ -18,51 +27,45 @@ pub fn character_plugin(app: &mut App) {
         .register_type::<Jumping>()
         .register_type::<Climber>()
         .register_type::<JumpSpeed>()
+        .register_type::<Facing>()
+        .register_type::<JumpControl>()
+        .register_type::<JumpTimings>()
+        .register_type::<WallDetection>()
+        .register_type::<FallDamage>()
+        .register_type::<FallTracker>()
         .add_systems(
-            Update,
+            FixedUpdate,
             (
                 update_on_ground,
+                update_on_wall,
                 update_in_water,
                 update_jumping,
                 ignore_gravity_if_climbing,
+                clamp_fall_speed,
+                fall_damage.after(update_on_ground).after(update_in_water),
             )
                 .in_set(InGameSet::EntityUpdate),
         )
         .add_systems(
-            Update,
-            (detect_climb_range, ground_detection).in_set(InGameSet::CollisionDetection),
+            FixedUpdate,
+            (detect_climb_range, ground_detection, wall_detection)
+                .in_set(InGameSet::CollisionDetection),
         );
 }
 
 fn detect_climb_range(
     mut climbers: Query<&mut Climber>,
-    climbables: Query<Entity, With<Climbable>>,
-    mut collisions: EventReader<CollisionEvent>,
+    mut collisions: Collisions<With<Climber>, With<Climbable>>,
 ) {
     for collision in collisions.read() {
         match collision {
-            CollisionEvent::Started(e1, e2, _) => {
-                if let (Ok(mut climber), Ok(climbable)) =
-                    (climbers.get_mut(*e1), climbables.get(*e2))
-                {
-                    climber.intersecting_climbables.insert(climbable);
-                }
-                if let (Ok(mut climber), Ok(climbable)) =
-                    (climbers.get_mut(*e2), climbables.get(*e1))
-                {
+            CollisionPair::Started(climber_entity, climbable) => {
+                if let Ok(mut climber) = climbers.get_mut(climber_entity) {
                     climber.intersecting_climbables.insert(climbable);
                 }
             }
-            CollisionEvent::Stopped(e1, e2, _) => {
-                if let (Ok(mut climber), Ok(climbable)) =
-                    (climbers.get_mut(*e1), climbables.get(*e2))
-                {
-                    climber.intersecting_climbables.remove(&climbable);
-                }
-
-                if let (Ok(mut climber), Ok(climbable)) =
-                    (climbers.get_mut(*e2), climbables.get(*e1))
-                {
+            CollisionPair::Stopped(climber_entity, climbable) => {
+                if let Ok(mut climber) = climbers.get_mut(climber_entity) {
                     climber.intersecting_climbables.remove(&climbable);
                 }
             }
@@ -82,37 +85,60 @@ fn ignore_gravity_if_climbing(mut query: Query<(&Climber, &mut GravityScale), Ch
 
 fn ground_detection(
     mut ground_sensors: Query<&mut GroundSensor>,
-    mut collisions: EventReader<CollisionEvent>,
-    collidables: Query<Entity, (With<Collider>, Without<Sensor>)>,
+    mut collisions: Collisions<With<GroundSensor>, (With<Collider>, Without<Sensor>)>,
 ) {
-    for collision_event in collisions.read() {
-        match collision_event {
-            CollisionEvent::Started(e1, e2, _) => {
-                if collidables.contains(*e1) {
-                    if let Ok(mut sensor) = ground_sensors.get_mut(*e2) {
-                        sensor.intersecting_ground_entities.insert(*e1);
-                    }
-                } else if collidables.contains(*e2) {
-                    if let Ok(mut sensor) = ground_sensors.get_mut(*e1) {
-                        sensor.intersecting_ground_entities.insert(*e2);
-                    }
+    for collision in collisions.read() {
+        match collision {
+            CollisionPair::Started(sensor_entity, ground) => {
+                if let Ok(mut sensor) = ground_sensors.get_mut(sensor_entity) {
+                    sensor.intersecting_ground_entities.insert(ground);
                 }
             }
-            CollisionEvent::Stopped(e1, e2, _) => {
-                if collidables.contains(*e1) {
-                    if let Ok(mut sensor) = ground_sensors.get_mut(*e2) {
-                        sensor.intersecting_ground_entities.remove(e1);
-                    }
-                } else if collidables.contains(*e2) {
-                    if let Ok(mut sensor) = ground_sensors.get_mut(*e1) {
-                        sensor.intersecting_ground_entities.remove(e2);
-                    }
+            CollisionPair::Stopped(sensor_entity, ground) => {
+                if let Ok(mut sensor) = ground_sensors.get_mut(sensor_entity) {
+                    sensor.intersecting_ground_entities.remove(&ground);
                 }
             }
         }
     }
 }
 
+fn wall_detection(
+    mut wall_sensors: Query<&mut WallSensor>,
+    mut collisions: Collisions<With<WallSensor>, With<LevelCollider>>,
+) {
+    for collision in collisions.read() {
+        match collision {
+            CollisionPair::Started(sensor_entity, wall) => {
+                if let Ok(mut sensor) = wall_sensors.get_mut(sensor_entity) {
+                    sensor.intersecting_wall_entities.insert(wall);
+                }
+            }
+            // Level colliders are respawned when digging, so the wall may not exist anymore
+            CollisionPair::Stopped(sensor_entity, wall) => {
+                if let Ok(mut sensor) = wall_sensors.get_mut(sensor_entity) {
+                    sensor.intersecting_wall_entities.remove(&wall);
+                }
+            }
+        }
+    }
+}
+
+fn update_on_wall(
+    mut wall_detectors: Query<&mut WallDetection>,
+    wall_sensors: Query<&WallSensor, Changed<WallSensor>>,
+) {
+    for sensor in &wall_sensors {
+        if let Ok(mut wall_detection) = wall_detectors.get_mut(sensor.wall_detection_entity) {
+            let on_wall = !sensor.intersecting_wall_entities.is_empty();
+            match sensor.side {
+                Facing::Left => wall_detection.on_left = on_wall,
+                Facing::Right => wall_detection.on_right = on_wall,
+            }
+        }
+    }
+}
+
 fn update_on_ground(
     mut ground_detectors: Query<&mut GroundDetection>,
     ground_sensors: Query<&GroundSensor, Changed<GroundSensor>>,
@@ -130,10 +156,9 @@ fn update_in_water(
     mut in_waters: Query<(&Transform, &mut InWater)>,
     ldtk_projects: Query<&LdtkProjectHandle>,
     level_query: Query<(Entity, &Transform, &LevelIid)>,
-    water_cells: Query<(&GridCoords, &ChildOf), With<LdtkWaterCell>>,
-    parents: Query<&ChildOf, Without<LdtkWaterCell>>,
     ldtk_project_assets: Res<Assets<LdtkProject>>,
     level_selection: Res<LevelSelection>,
+    level_grid: Res<LevelGrid>,
 ) {
     let Ok(ldtk_project) = ldtk_projects.single() else {
         return;
@@ -155,16 +180,12 @@ fn update_in_water(
                 let character_coord =
                     translation_to_grid_coords(translation, IVec2::splat(layer_info.grid_size));
 
-                in_water.0 = water_cells.iter().any(|(&coord, &ChildOf(parent))| {
-                    if coord == character_coord {
-                        if let Ok(&ChildOf(grandparent)) = parents.get(parent) {
-                            if grandparent == level_entity {
-                                return true;
-                            }
-                        }
-                    }
-                    false
-                });
+                // Only changed when entering or leaving water
+                in_water.set_if_neq(InWater(
+                    level_grid
+                        .cell_at(level_entity, character_coord)
+                        .is_some_and(|cell| cell.kind == CellKind::Water),
+                ));
             });
     }
 }
@@ -178,3 +199,88 @@ fn update_jumping(
         }
     }
 }
+
+/// Falling characters can't go faster than a terminal velocity
+fn clamp_fall_speed(mut fallers: Query<&mut Velocity, With<FallTracker>>) {
+    const TERMINAL_VELOCITY: f32 = 420.;
+
+    for mut velocity in &mut fallers {
+        if velocity.linvel.y < -TERMINAL_VELOCITY {
+            velocity.linvel.y = -TERMINAL_VELOCITY;
+        }
+    }
+}
+
+/// Track the height of a fall, and hurt the character when it lands from too high.
+///
+/// Falls that end in water, while climbing, after sliding along a wall, or while the
+/// character is [Invulnerable], are harmless.
+fn fall_damage(
+    mut commands: Commands,
+    mut fallers: Query<
+        (
+            Entity,
+            &Transform,
+            &GroundDetection,
+            &InWater,
+            &Climber,
+            &FallDamage,
+            &mut FallTracker,
+            &mut Life,
+            Has<Invulnerable>,
+        ),
+        Without<Dying>,
+    >,
+) {
+    const HURT_STUN_SECS: f32 = 0.3;
+    // Each extra tile over the damage height costs one more life
+    const HEIGHT_PER_DAMAGE: f32 = 16.;
+
+    for (
+        entity,
+        transform,
+        ground_detection,
+        &in_water,
+        climber,
+        fall_damage,
+        mut tracker,
+        mut life,
+        invulnerable,
+    ) in &mut fallers
+    {
+        let y = transform.translation.y;
+        if *in_water || climber.climbing {
+            tracker.peak_y = None;
+        } else if ground_detection.on_ground {
+            // The fall is over even when it is harmless
+            let Some(peak_y) = tracker.peak_y.take() else {
+                continue;
+            };
+            if invulnerable {
+                continue;
+            }
+            let height = peak_y - y;
+            if height >= fall_damage.death_height {
+                info!("Fatal fall of {height}px");
+                life.hit(life.get());
+            } else if height >= fall_damage.damage_height {
+                info!("Hurting fall of {height}px");
+                let damage = 1 + ((height - fall_damage.damage_height) / HEIGHT_PER_DAMAGE) as u16;
+                life.hit(damage);
+            } else {
+                continue;
+            }
+
+            commands.trigger(ImpactEvent::HeavyLanding);
+            if life.is_dead() {
+                commands.entity(entity).insert(Dying);
+            } else {
+                commands
+                    .entity(entity)
+                    .insert(HitStun::new(Duration::from_secs_f32(HURT_STUN_SECS)));
+            }
+        } else {
+            tracker.peak_y = Some(tracker.peak_y.map_or(y, |peak_y| peak_y.max(y)));
+        }
+    }
+}
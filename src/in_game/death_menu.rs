@@ -1,5 +1,8 @@
 use crate::{
-    components::player::PlayerDeathEvent,
+    components::{
+        animation::ClipFinishedEvent,
+        player::{Player, PLAYER_DEATH_CLIP},
+    },
     cursor::ungrab_cursor,
     schedule::{GameState, InGameState},
     theme::widget,
@@ -41,11 +44,15 @@ fn spawn_death_menu(mut commands: Commands) {
     commands.spawn((death_menu(), StateScoped(InGameState::PlayerDied)));
 }
 
+/// The player is dead when its death animation is over
 fn on_player_death(
-    _trigger: Trigger<PlayerDeathEvent>,
+    trigger: Trigger<ClipFinishedEvent>,
+    players: Query<(), With<Player>>,
     mut in_game_state: ResMut<NextState<InGameState>>,
 ) {
-    in_game_state.set(InGameState::PlayerDied);
+    if trigger.clip == PLAYER_DEATH_CLIP && players.contains(trigger.target()) {
+        in_game_state.set(InGameState::PlayerDied);
+    }
 }
 
 fn back_to_menu(
@@ -1,27 +1,149 @@
 use crate::{
+    components::{
+        high_score::{level_version, HighScore, HighScores, MAX_NAME_LEN},
+        score::{LevelScoring, Score},
+    },
     schedule::{GameState, InGameState},
-    theme::widget,
+    theme::{text_input::TextInputSubmitEvent, widget},
 };
-use bevy::prelude::*;
+use bevy::{ecs::spawn::SpawnWith, prelude::*};
+use bevy_ecs_ldtk::prelude::*;
 
 pub fn end_level_menu_plugin(app: &mut App) {
-    app.add_systems(OnEnter(InGameState::PlayerEndedLevel), spawn_menu);
+    app.add_systems(OnEnter(InGameState::PlayerEndedLevel), spawn_menu)
+        .add_observer(save_high_score);
 }
 
 #[derive(Component)]
 struct EndLevelMenu;
 
-fn end_level_menu() -> impl Bundle {
+/// A score that enters the high scores, waiting for the player name
+#[derive(Component, Clone)]
+struct PendingHighScore {
+    level: String,
+    level_version: i32,
+    score: u32,
+}
+
+fn end_level_menu(
+    score: &Score,
+    scoring: &LevelScoring,
+    pending_high_score: Option<PendingHighScore>,
+) -> impl Bundle {
+    let total = score.total(scoring);
+    let stars = scoring.stars(total) as usize;
     (
         EndLevelMenu,
         Name::new("EndLevelMenu"),
-        widget::centered(),
-        children![widget::menu_button("Quit game", on_quit_game)],
+        widget::popup(),
+        Children::spawn((
+            Spawn(widget::popup_title("Level completed")),
+            Spawn(widget::label(format!(
+                "Gems: {} ({} pts)",
+                score.gems,
+                score.gem_points()
+            ))),
+            Spawn(widget::label(format!(
+                "Enemies: {} ({} pts)",
+                score.enemies,
+                score.enemy_points()
+            ))),
+            Spawn(widget::label(format!(
+                "Chests: {} ({} pts)",
+                score.chests,
+                score.chest_points()
+            ))),
+            Spawn(widget::label(format!(
+                "Time: {:.1}s ({} pts)",
+                score.elapsed_secs,
+                score.time_bonus(scoring)
+            ))),
+            Spawn(widget::label(format!("Total: {total}"))),
+            Spawn(widget::label(format!(
+                "Rating: {}{}",
+                "*".repeat(stars),
+                "-".repeat(3 - stars)
+            ))),
+            SpawnWith(move |parent: &mut ChildSpawner| {
+                if let Some(pending_high_score) = pending_high_score {
+                    parent.spawn(high_score_entry(pending_high_score));
+                }
+            }),
+            Spawn(widget::menu_button("Quit game", on_quit_game)),
+        )),
+    )
+}
+
+fn high_score_entry(pending_high_score: PendingHighScore) -> impl Bundle {
+    (
+        Name::new("HighScoreEntry"),
+        widget::vsizer(),
+        children![
+            widget::label("New high score! Enter your name:"),
+            (widget::text_input("", MAX_NAME_LEN), pending_high_score),
+        ],
     )
 }
 
-fn spawn_menu(mut commands: Commands) {
-    commands.spawn((end_level_menu(), StateScoped(InGameState::PlayerEndedLevel)));
+fn spawn_menu(
+    mut commands: Commands,
+    score: Res<Score>,
+    high_scores: Res<HighScores>,
+    ldtk_projects: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    level_selection: Res<LevelSelection>,
+) {
+    let level = ldtk_projects
+        .single()
+        .ok()
+        .and_then(|handle| ldtk_project_assets.get(handle))
+        .and_then(|ldtk_project| {
+            ldtk_project
+                .iter_raw_levels()
+                .find(|level| level_selection.is_match(&LevelIndices::default(), level))
+        });
+    let scoring = level.map(LevelScoring::from).unwrap_or_default();
+    let total = score.total(&scoring);
+    let pending_high_score = level
+        .map(|level| PendingHighScore {
+            level: level.identifier.clone(),
+            level_version: level_version(level),
+            score: total,
+        })
+        .filter(|pending| high_scores.qualifies(&pending.level, pending.level_version, total));
+
+    commands.spawn((
+        end_level_menu(&score, &scoring, pending_high_score),
+        StateScoped(InGameState::PlayerEndedLevel),
+    ));
+}
+
+fn save_high_score(
+    trigger: Trigger<TextInputSubmitEvent>,
+    mut commands: Commands,
+    pending_high_scores: Query<(&PendingHighScore, &ChildOf)>,
+    mut high_scores: ResMut<HighScores>,
+) -> Result {
+    let Ok((pending_high_score, child_of)) = pending_high_scores.get(trigger.target()) else {
+        return Ok(());
+    };
+    let name = trigger.0.trim();
+    if name.is_empty() {
+        return Ok(());
+    }
+    high_scores.insert(
+        &pending_high_score.level,
+        pending_high_score.level_version,
+        HighScore {
+            name: name.to_string(),
+            score: pending_high_score.score,
+        },
+    );
+    commands
+        .entity(child_of.parent())
+        .despawn_related::<Children>()
+        .with_child(widget::label("High score saved"));
+    high_scores.save()
 }
 
 fn on_quit_game(
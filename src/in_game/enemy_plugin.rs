@@ -1,5 +1,10 @@
 use crate::{
-    components::{character::Speed, enemy::Patrol},
+    asset_tracking::LoadResource,
+    components::{
+        animation::{ClipFinishedEvent, SpriteAnimation},
+        character::Speed,
+        enemy::{Enemy, EnemyAssets, EnemyDeathEvent, Patrol, ENEMY_DEATH_CLIP},
+    },
     schedule::InGameSet,
 };
 use bevy::prelude::*;
@@ -7,7 +12,49 @@ use bevy_rapier2d::prelude::*;
 
 pub fn enemy_plugin(app: &mut App) {
     app.register_type::<Patrol>()
-        .add_systems(Update, patrol.in_set(InGameSet::EntityUpdate));
+        .load_resource::<EnemyAssets>()
+        .add_systems(FixedUpdate, patrol.in_set(InGameSet::EntityUpdate))
+        .add_observer(init_enemy_animation)
+        .add_observer(enemy_death)
+        .add_observer(despawn_dead_enemy);
+}
+
+fn init_enemy_animation(
+    trigger: Trigger<OnAdd, Enemy>,
+    mut commands: Commands,
+    assets: Res<EnemyAssets>,
+) {
+    commands
+        .entity(trigger.target())
+        .insert(SpriteAnimation::new(assets.animations.clone(), "walk"));
+}
+
+/// The enemy stops and can't be touched while its death animation plays
+fn enemy_death(
+    trigger: Trigger<EnemyDeathEvent>,
+    mut commands: Commands,
+    mut animations: Query<&mut SpriteAnimation, With<Enemy>>,
+) {
+    info!("Enemy killed");
+    let Ok(mut animation) = animations.get_mut(trigger.target()) else {
+        commands.entity(trigger.target()).try_despawn();
+        return;
+    };
+    animation.play(ENEMY_DEATH_CLIP);
+    commands
+        .entity(trigger.target())
+        .remove::<(Collider, Patrol)>()
+        .insert(Velocity::zero());
+}
+
+fn despawn_dead_enemy(
+    trigger: Trigger<ClipFinishedEvent>,
+    mut commands: Commands,
+    enemies: Query<(), With<Enemy>>,
+) {
+    if trigger.clip == ENEMY_DEATH_CLIP && enemies.contains(trigger.target()) {
+        commands.entity(trigger.target()).try_despawn();
+    }
 }
 
 fn patrol(mut query: Query<(&mut Transform, &mut Velocity, &Speed, &mut Patrol)>) {
@@ -0,0 +1,158 @@
+use crate::{
+    components::{
+        animation::{SpriteAnimation, SpriteClips},
+        despawn_all,
+        ghost::{
+            Ghost, GhostFrame, GhostRecording, GhostRun, GhostSettings, LoadedGhost, GHOST_ALPHA,
+        },
+        player::{Player, PlayerAssets},
+    },
+    schedule::{game_is_running, GameState, InGameSet, InGameState},
+    utils::args::arg_value,
+};
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+pub fn ghost_plugin(app: &mut App) {
+    if let Some(path) = arg_value("--ghost") {
+        match GhostRun::load(&path) {
+            Ok(run) => {
+                info!("Race against {path} in level {}", run.level);
+                app.insert_resource(LoadedGhost(run));
+            }
+            Err(e) => error!("Can't load ghost {path}, start without it: {e}"),
+        }
+    }
+
+    app.register_type::<GhostSettings>()
+        .init_resource::<GhostSettings>()
+        .init_resource::<GhostRecording>()
+        .add_systems(
+            OnEnter(InGameState::LevelLoading),
+            (reset_recording, despawn_all::<Ghost>),
+        )
+        .add_systems(OnExit(GameState::InGame), despawn_all::<Ghost>)
+        .add_systems(OnEnter(InGameState::Running), spawn_ghost)
+        .add_systems(
+            FixedUpdate,
+            (record_player_sprite, move_ghost)
+                .chain()
+                .after(InGameSet::CollisionDetection)
+                .run_if(game_is_running),
+        )
+        .add_systems(OnEnter(InGameState::PlayerEndedLevel), save_best_run);
+}
+
+fn reset_recording(mut recording: ResMut<GhostRecording>) {
+    recording.0 = GhostRun::default();
+}
+
+/// Spawn the ghost of the best run of the level, when the game starts or
+/// when the ghost is enabled from the pause menu
+fn spawn_ghost(
+    mut commands: Commands,
+    mut recording: ResMut<GhostRecording>,
+    settings: Res<GhostSettings>,
+    loaded_ghost: Option<Res<LoadedGhost>>,
+    ghosts: Query<(), With<Ghost>>,
+    players: Query<&Transform, With<Player>>,
+    ldtk_projects: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    level_selection: Res<LevelSelection>,
+) {
+    if recording.0.level.is_empty() {
+        let Some(level) = ldtk_projects
+            .single()
+            .ok()
+            .and_then(|handle| ldtk_project_assets.get(handle))
+            .and_then(|ldtk_project| {
+                ldtk_project
+                    .iter_raw_levels()
+                    .find(|level| level_selection.is_match(&LevelIndices::default(), level))
+            })
+        else {
+            return;
+        };
+        recording.0.level = level.identifier.clone();
+    }
+
+    if !settings.enabled || !ghosts.is_empty() {
+        return;
+    }
+    let level = &recording.0.level;
+    let Some(run) = loaded_ghost
+        .map(|loaded_ghost| loaded_ghost.0.clone())
+        .filter(|run| run.level == *level)
+        .or_else(|| GhostRun::load_best(level))
+    else {
+        return;
+    };
+    // Just behind the player
+    let z = players
+        .single()
+        .map(|transform| transform.translation.z - 0.1)
+        .unwrap_or_default();
+    commands.spawn((
+        Ghost(run),
+        Transform::from_xyz(0., 0., z),
+        Visibility::Hidden,
+    ));
+}
+
+fn record_player_sprite(
+    mut recording: ResMut<GhostRecording>,
+    players: Query<(&Transform, &Sprite, &SpriteAnimation), With<Player>>,
+) {
+    if let Ok((transform, sprite, animation)) = players.single() {
+        recording.0.frames.push(GhostFrame {
+            translation: transform.translation.xy().into(),
+            clip: animation.clip().into(),
+            index: sprite
+                .texture_atlas
+                .as_ref()
+                .map(|atlas| atlas.index)
+                .unwrap_or_default(),
+        });
+    }
+}
+
+/// Show the ghost as the player was at the same time of its run
+fn move_ghost(
+    mut ghosts: Query<(&Ghost, &mut Transform, &mut Sprite, &mut Visibility)>,
+    recording: Res<GhostRecording>,
+    assets: Res<PlayerAssets>,
+    sprite_clips: Res<Assets<SpriteClips>>,
+) {
+    let step = recording.0.frames.len().saturating_sub(1);
+    let clips = sprite_clips.get(&assets.animations);
+    for (ghost, mut transform, mut sprite, mut visibility) in &mut ghosts {
+        // Stay at the end of the run when it is over
+        let Some(frame) = ghost.0.frames.get(step).or(ghost.0.frames.last()) else {
+            continue;
+        };
+        transform.translation = Vec2::from(frame.translation).extend(transform.translation.z);
+        if let Some(clip) = clips.and_then(|clips| clips.get(&frame.clip)) {
+            *sprite = Sprite::from_atlas_image(
+                clip.image.clone(),
+                TextureAtlas {
+                    layout: clip.layout.clone(),
+                    index: frame.index,
+                },
+            );
+            sprite.color = Color::WHITE.with_alpha(GHOST_ALPHA);
+        }
+        *visibility = Visibility::Inherited;
+    }
+}
+
+fn save_best_run(recording: Res<GhostRecording>) -> Result {
+    let run = &recording.0;
+    if run.level.is_empty() || run.frames.is_empty() {
+        return Ok(());
+    }
+    if GhostRun::load_best(&run.level).is_none_or(|best| run.frames.len() < best.frames.len()) {
+        info!("New best run of level {}", run.level);
+        run.save_best()?;
+    }
+    Ok(())
+}
@@ -4,8 +4,10 @@ use crate::{
         despawn_all,
         item::{ItemAssets, Items},
         player::Player,
+        score::Score,
+        speedrun::{format_delta, format_time, PersonalBests, ShowSplits, Speedrun},
     },
-    schedule::{GameState, InGameSet},
+    schedule::{GameState, InGameState},
     theme::widget,
     ui::progressbar::ProgressBar,
 };
@@ -14,12 +16,23 @@ use bevy::prelude::*;
 pub fn hud_plugin(app: &mut App) {
     app.add_systems(
         OnEnter(GameState::InGame),
-        (spawn_player_items, spawn_player_life),
+        (
+            spawn_player_items,
+            spawn_player_life,
+            spawn_score,
+            spawn_speedrun,
+        ),
     )
     .add_systems(OnExit(GameState::InGame), despawn_all::<Hud>)
     .add_systems(
         Update,
-        (update_player_items, update_player_life).in_set(InGameSet::EntityUpdate),
+        (
+            update_player_items,
+            update_player_life,
+            update_score,
+            update_speedrun,
+        )
+            .run_if(in_state(InGameState::Running)),
     );
 }
 
@@ -32,6 +45,12 @@ struct HudPlayerItems;
 #[derive(Component)]
 struct HudPlayerLife;
 
+#[derive(Component)]
+struct HudScore;
+
+#[derive(Component)]
+struct HudSpeedrun;
+
 fn spawn_player_items(mut commands: Commands) {
     commands.spawn((
         Name::new("HudPlayerItems"),
@@ -60,7 +79,39 @@ fn spawn_player_life(mut commands: Commands) {
             height: Val::Px(16.0),
             ..Default::default()
         },
-        ProgressBar::new(0.0, 10.0, 10.0).with_colors(Color::BLACK, Srgba::RED.into()),
+        ProgressBar::new(0.0, 10.0, 10.0)
+            .with_colors(Color::BLACK, Srgba::RED.into())
+            .with_trail(Color::WHITE),
+    ));
+}
+
+fn spawn_score(mut commands: Commands) {
+    commands.spawn((
+        Name::new("HudScore"),
+        HudScore,
+        Hud,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Percent(45.0),
+            ..Default::default()
+        },
+        widget::label(""),
+    ));
+}
+
+fn spawn_speedrun(mut commands: Commands) {
+    commands.spawn((
+        Name::new("HudSpeedrun"),
+        HudSpeedrun,
+        Hud,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(36.0),
+            right: Val::Px(10.0),
+            ..Default::default()
+        },
+        widget::label(""),
     ));
 }
 
@@ -91,8 +142,56 @@ fn update_player_life(
     mut progressbars: Query<&mut ProgressBar, With<HudPlayerLife>>,
 ) {
     if let Ok(life) = life.single() {
+        let (value, max) = (life.get() as f32, life.max() as f32);
         for mut progressbar in progressbars.iter_mut() {
-            progressbar.set_value(life.get() as f32);
+            if progressbar.value != value || progressbar.max != max {
+                progressbar.set_range(0.0, max);
+                progressbar.set_value(value);
+                // One heart per life point
+                progressbar.segments = life.max().into();
+            }
+        }
+    }
+}
+
+fn update_score(score: Res<Score>, mut texts: Query<&mut Text, With<HudScore>>) {
+    if score.is_changed() {
+        for mut text in &mut texts {
+            text.0 = format!(
+                "Score: {}  Time: {:.0}s",
+                score.points(),
+                score.elapsed_secs
+            );
         }
     }
 }
+
+fn update_speedrun(
+    speedrun: Res<Speedrun>,
+    personal_bests: Res<PersonalBests>,
+    show_splits: Res<ShowSplits>,
+    mut texts: Query<&mut Text, With<HudSpeedrun>>,
+) {
+    if !speedrun.is_changed() && !show_splits.is_changed() {
+        return;
+    }
+    let mut lines = Vec::new();
+    if show_splits.0 {
+        for (index, split) in speedrun.splits.iter().enumerate() {
+            let mut line = format!("{}  {}", split.level, format_time(split.run_secs));
+            if let Some(best) = personal_bests.compared_split(index, &split.level) {
+                line += &format!("  {}", format_delta(split.run_secs, best.run_secs));
+            }
+            lines.push(line);
+        }
+    }
+    let mut line = format_time(speedrun.run.elapsed_secs());
+    if let Some(best_secs) = personal_bests.run_secs() {
+        line += &format!("  PB {}", format_time(best_secs));
+    }
+    lines.push(line);
+
+    for mut text in &mut texts {
+        text.0 = lines.join("\n");
+    }
+}
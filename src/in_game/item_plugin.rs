@@ -1,12 +1,13 @@
 use crate::{
     asset_tracking::LoadResource,
     components::{
-        item::{Chest, ItemAssets, Items},
+        animation::{ClipFinishedEvent, SpriteAnimation},
+        item::{Chest, ChestOpenedEvent, ItemAssets, Items, OpenedChest, CHEST_OPEN_CLIP},
         player::Player,
     },
     in_game::popup_with_images::*,
     schedule::InGameSet,
-    utils::collisions::{start_event_filter, QueryEither},
+    utils::collisions::Collisions,
 };
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
@@ -14,37 +15,68 @@ use bevy_rapier2d::prelude::*;
 pub fn item_plugin(app: &mut App) {
     app.register_type::<Items>()
         .load_resource::<ItemAssets>()
-        .add_systems(Update, open_chest.in_set(InGameSet::CollisionDetection));
+        .add_systems(
+            FixedUpdate,
+            open_chest.in_set(InGameSet::CollisionDetection),
+        )
+        .add_observer(init_chest_animation)
+        .add_observer(despawn_opened_chest);
+}
+
+fn init_chest_animation(
+    trigger: Trigger<OnAdd, Chest>,
+    mut commands: Commands,
+    assets: Res<ItemAssets>,
+) {
+    commands
+        .entity(trigger.target())
+        .insert(SpriteAnimation::new(
+            assets.chest_animations.clone(),
+            "closed",
+        ));
+}
+
+fn despawn_opened_chest(
+    trigger: Trigger<ClipFinishedEvent>,
+    mut commands: Commands,
+    chests: Query<(), With<OpenedChest>>,
+) {
+    if trigger.clip == CHEST_OPEN_CLIP && chests.contains(trigger.target()) {
+        commands.entity(trigger.target()).try_despawn();
+    }
 }
 
 fn open_chest(
     mut commands: Commands,
-    mut collisions: EventReader<CollisionEvent>,
-    mut players: Query<(Entity, &mut Items), With<Player>>,
-    chests: Query<&Items, (With<Chest>, Without<Player>)>,
+    mut collisions: Collisions<With<Player>, With<Chest>>,
+    mut players: Query<&mut Items, With<Player>>,
+    mut chests: Query<(&Items, &mut SpriteAnimation), (With<Chest>, Without<Player>)>,
     assets: Res<ItemAssets>,
 ) {
-    let (player_entity, mut player_items) = players.single_mut().expect("Player");
-    collisions
-        .read()
-        .filter_map(start_event_filter)
-        .filter_map(|(&e1, &e2)| chests.get_either(e1, e2))
-        .filter(|(_items, _chest_entity, other_entity)| player_entity == *other_entity)
-        .for_each(|(chest_items, chest_entity, _player_entity)| {
-            info!("Player open chest");
-            // Player get chest items
-            for i in chest_items.iter() {
-                player_items.add(*i);
-            }
+    let mut player_items = players.single_mut().expect("Player");
+    for (_player_entity, chest_entity) in collisions.started() {
+        let Ok((chest_items, mut animation)) = chests.get_mut(chest_entity) else {
+            continue;
+        };
+        info!("Player open chest");
+        // Player get chest items
+        for i in chest_items.iter() {
+            player_items.add(*i);
+        }
 
-            // Remove the chest
-            commands.entity(chest_entity).despawn();
+        // It can't be opened again, and is removed once its opening animation is over
+        commands.trigger_targets(ChestOpenedEvent(chest_items.clone()), chest_entity);
+        commands
+            .entity(chest_entity)
+            .remove::<(Chest, Collider)>()
+            .insert(OpenedChest);
+        animation.play(CHEST_OPEN_CLIP);
 
-            // Show a popup with chest items
-            let images = chest_items
-                .iter()
-                .map(|&i| assets.image_node(i))
-                .collect::<Vec<_>>();
-            commands.spawn(popup_with_images("Chest opened", "You found", images));
-        });
+        // Show a popup with chest items
+        let images = chest_items
+            .iter()
+            .map(|&i| assets.image_node(i))
+            .collect::<Vec<_>>();
+        commands.spawn(popup_with_images("Chest opened", "You found", images));
+    }
 }
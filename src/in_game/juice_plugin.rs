@@ -0,0 +1,110 @@
+use crate::{
+    components::{
+        enemy::EnemyDeathEvent,
+        juice::{CameraShake, HitStop, ImpactEvent, ShakeSettings},
+    },
+    schedule::GameState,
+};
+use bevy::prelude::*;
+
+/// Maximum offset of the [CameraShake], in pixels
+const MAX_SHAKE_OFFSET: f32 = 6.;
+/// Trauma lost per second
+const TRAUMA_DECAY: f32 = 1.5;
+/// Speed of the game time during a [HitStop]
+const HIT_STOP_SPEED: f32 = 0.05;
+
+pub fn juice_plugin(app: &mut App) {
+    app.register_type::<ShakeSettings>()
+        .register_type::<CameraShake>()
+        .init_resource::<ShakeSettings>()
+        .init_resource::<HitStop>()
+        .add_systems(First, remove_camera_shake)
+        .add_systems(
+            PostUpdate,
+            shake_camera.before(TransformSystem::TransformPropagate),
+        )
+        .add_systems(Update, hit_stop)
+        .add_systems(OnExit(GameState::InGame), reset_juice)
+        .add_observer(on_impact)
+        .add_observer(enemy_killed);
+}
+
+fn on_impact(
+    trigger: Trigger<ImpactEvent>,
+    mut shakes: Query<&mut CameraShake>,
+    settings: Res<ShakeSettings>,
+    mut hit_stop: ResMut<HitStop>,
+) {
+    let impact = trigger.event();
+    if settings.enabled {
+        for mut shake in &mut shakes {
+            shake.add_trauma(impact.trauma());
+        }
+    }
+    hit_stop.remaining_secs = hit_stop.remaining_secs.max(impact.hit_stop_secs());
+}
+
+fn enemy_killed(_trigger: Trigger<EnemyDeathEvent>, mut commands: Commands) {
+    commands.trigger(ImpactEvent::EnemyKilled);
+}
+
+/// Put the camera back where it was moved
+fn remove_camera_shake(mut cameras: Query<(&mut Transform, &mut CameraShake)>) {
+    for (mut transform, mut shake) in &mut cameras {
+        if shake.offset != Vec2::ZERO {
+            transform.translation -= shake.offset.extend(0.);
+            shake.offset = Vec2::ZERO;
+        }
+    }
+}
+
+fn shake_camera(
+    mut cameras: Query<(&mut Transform, &mut CameraShake)>,
+    settings: Res<ShakeSettings>,
+    time: Res<Time<Real>>,
+) {
+    for (mut transform, mut shake) in &mut cameras {
+        shake.trauma = (shake.trauma - TRAUMA_DECAY * time.delta_secs()).max(0.);
+        if !settings.enabled || shake.trauma == 0. {
+            continue;
+        }
+        // Smooth pseudo random directions
+        let t = time.elapsed_secs();
+        let noise = Vec2::new(
+            (t * 47.).sin() + (t * 23.).cos(),
+            (t * 41.).cos() + (t * 29.).sin(),
+        );
+        shake.offset = noise / 2. * MAX_SHAKE_OFFSET * shake.trauma * shake.trauma;
+        transform.translation += shake.offset.extend(0.);
+    }
+}
+
+/// Slow down the game, the hit-stop lasting in real time
+fn hit_stop(
+    mut hit_stop: ResMut<HitStop>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    real_time: Res<Time<Real>>,
+) {
+    let speed = if hit_stop.remaining_secs > 0. {
+        hit_stop.remaining_secs -= real_time.delta_secs();
+        HIT_STOP_SPEED
+    } else {
+        1.
+    };
+    if virtual_time.relative_speed() != speed {
+        virtual_time.set_relative_speed(speed);
+    }
+}
+
+fn reset_juice(
+    mut hit_stop: ResMut<HitStop>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut shakes: Query<&mut CameraShake>,
+) {
+    hit_stop.remaining_secs = 0.;
+    virtual_time.set_relative_speed(1.);
+    for mut shake in &mut shakes {
+        shake.trauma = 0.;
+    }
+}
@@ -1,20 +1,24 @@
 use crate::{
     components::{
         enemy::LdtkMobBundle,
+        input::{GameInput, GameKey},
         item::{ItemAssets, Items, LdtkChestBundle},
+        juice::ImpactEvent,
         level::{
-            level_collider, ColliderCell, Destroyed, Destructible, Door, EndLevel, LdtkDirtCell,
-            LdtkDoorBundle, LdtkEndLevelBundle, LdtkLadderCell, LdtkStoneCell, LdtkWaterCell,
-            LevelCollider, LevelColliders, LevelData, LoadedLevelExt, UpdateCollidersEvent,
-            DIRT_INT_CELL, LADDER_INT_CELL, STONE_INT_CELL, WATER_INT_CELL,
+            level_collider, level_start, ColliderCell, Destroyed, Destructible, Door, EndLevel,
+            LdtkDirtCell, LdtkDoorBundle, LdtkEndLevelBundle, LdtkLadderCell, LdtkProjectPath,
+            LdtkStoneCell, LdtkWaterCell, LevelCollider, LevelColliders, LevelData,
+            LevelTransition, LevelTransitionKind, LevelTransitionSettings, LoadedLevelExt,
+            UpdateCollidersEvent, DIRT_INT_CELL, LADDER_INT_CELL, STONE_INT_CELL, WATER_INT_CELL,
         },
-        player::{DigEvent, LdtkPlayerBundle, Player},
+        level_grid::{GridCellData, LevelGrid},
+        player::{DigEvent, LdtkPlayerBundle, Player, PLAYER_HALF_SIZE},
     },
     in_game::popup_with_images::popup_with_images,
     schedule::{GameState, InGameSet, InGameState},
     theme::widget,
     ui::fade::{fader, FaderFinishEvent},
-    utils::collisions::{start_event_filter, QueryEither},
+    utils::{collisions::Collisions, grid_rectangles::grid_rectangles},
 };
 use bevy::{ecs::query::QuerySingleError, prelude::*};
 use bevy_ecs_ldtk::prelude::*;
@@ -25,6 +29,11 @@ use std::collections::HashSet;
 pub fn level_plugin(app: &mut App) {
     app.add_plugins(LdtkPlugin)
         .insert_resource(LevelSelection::Uid(0))
+        .init_resource::<LdtkProjectPath>()
+        .register_type::<LevelTransitionSettings>()
+        .init_resource::<LevelTransitionSettings>()
+        .register_type::<LevelGrid>()
+        .init_resource::<LevelGrid>()
         .insert_resource(LdtkSettings {
             level_spawn_behavior: LevelSpawnBehavior::UseWorldTranslation {
                 load_level_neighbors: true,
@@ -51,21 +60,33 @@ pub fn level_plugin(app: &mut App) {
             wait_for_end_of_level_loading.run_if(in_state(InGameState::LevelLoading)),
         )
         // LevelLoading
-        .add_systems(OnEnter(InGameState::LevelLoaded), show_level)
+        .add_systems(
+            OnEnter(InGameState::LevelLoaded),
+            (show_level, move_player_to_level_start),
+        )
         .add_systems(
             Update,
-            initialize_level_collisions.run_if(in_state(GameState::InGame)),
+            (initialize_level_collisions, index_level_cells).run_if(in_state(GameState::InGame)),
         )
+        .add_systems(OnExit(GameState::InGame), clear_level_grid)
         // InGame
         .add_systems(
-            Update,
+            FixedUpdate,
             update_level_based_on_player_pos.in_set(InGameSet::EntityUpdate),
         )
         .add_systems(
-            Update,
+            FixedUpdate,
             (open_door, end_level).in_set(InGameSet::CollisionDetection),
         )
-        .add_systems(Update, restart_level.in_set(InGameSet::UserInput))
+        .add_systems(FixedUpdate, restart_level.in_set(InGameSet::UserInput))
+        .add_systems(
+            OnEnter(InGameState::LevelTransition),
+            start_level_transition,
+        )
+        .add_systems(
+            Update,
+            tick_level_transition.run_if(in_state(InGameState::LevelTransition)),
+        )
         .add_observer(run_level_after_fading)
         .add_observer(on_dig)
         .add_observer(recalculate_level_collisions);
@@ -88,19 +109,28 @@ fn spawn_loading_screen(mut commands: Commands) {
     commands.spawn((loading_screen(), StateScoped(InGameState::LevelLoading)));
 }
 
+/// Fader from the loading screen to the level
+#[derive(Component)]
+struct LevelFader;
+
 fn show_level(mut commands: Commands) {
-    commands.spawn(fader(LOADING_SCREEN_BACKGROUND_COLOR, Color::NONE, 2.0));
+    commands.spawn((
+        fader(LOADING_SCREEN_BACKGROUND_COLOR, Color::NONE, 2.0),
+        LevelFader,
+    ));
 }
 
 /// wait for fader to finish, and start running game
 fn run_level_after_fading(
     trigger: Trigger<FaderFinishEvent>,
     mut commands: Commands,
+    level_faders: Query<(), With<LevelFader>>,
     mut in_game_state: ResMut<NextState<InGameState>>,
 ) {
-    if let Ok(mut fader) = commands.get_entity(trigger.target()) {
-        fader.despawn();
+    if !level_faders.contains(trigger.target()) {
+        return;
     }
+    commands.entity(trigger.target()).despawn();
     in_game_state.set(InGameState::Running);
 }
 
@@ -108,6 +138,7 @@ fn spawn_level(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     ldtk_projects: Query<Entity, With<LdtkProjectHandle>>,
+    ldtk_project_path: Res<LdtkProjectPath>,
 ) {
     match ldtk_projects.single() {
         Ok(world_entity) => {
@@ -118,7 +149,7 @@ fn spawn_level(
             // Spawn a new project
             commands.spawn((
                 LdtkWorldBundle {
-                    ldtk_handle: asset_server.load("load-runner.ldtk").into(),
+                    ldtk_handle: asset_server.load(&ldtk_project_path.0).into(),
                     ..Default::default()
                 },
                 Name::new("MapWorld"),
@@ -153,6 +184,32 @@ fn wait_for_end_of_level_loading(
     }
 }
 
+/// The player is spawned in the first level, move it to the start of the selected level
+fn move_player_to_level_start(
+    levels: Query<(&LevelIid, &Transform), Without<Player>>,
+    mut players: Query<&mut Transform, With<Player>>,
+    ldtk_projects: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    level_selection: Res<LevelSelection>,
+) -> Result {
+    let ldtk_project = ldtk_project_assets
+        .get(ldtk_projects.single()?)
+        .ok_or("Project should be loaded if level has spawned")?;
+    let grid_size = ldtk_project.json_data().default_grid_size;
+
+    let Some(start) = levels.iter().find_map(|(liid, transform)| {
+        let level = ldtk_project
+            .get_raw_level_by_iid(&liid.to_string())
+            .filter(|level| level_selection.is_match(&LevelIndices::default(), level))?;
+        level_start(level, grid_size).map(|start| transform.translation.xy() + start)
+    }) else {
+        return Ok(());
+    };
+    let mut player_transform = players.single_mut()?;
+    player_transform.translation = start.extend(player_transform.translation.z);
+    Ok(())
+}
+
 /// Spawns collisions for the walls of a level
 ///
 /// You could just insert a Collider in to the WallBundle,
@@ -192,20 +249,62 @@ fn initialize_level_collisions(
     Ok(())
 }
 
+/// Keep the [LevelGrid] in sync with the spawned levels
+fn index_level_cells(
+    mut events: EventReader<LevelEvent>,
+    mut level_grid: ResMut<LevelGrid>,
+    levels: Query<(Entity, &LevelIid)>,
+    cells: Query<GridCellData>,
+    parents: Query<&ChildOf, Without<GridCoords>>,
+) {
+    for event in events.read() {
+        match event {
+            LevelEvent::Spawned(liid) => {
+                let Some(level_entity) = levels
+                    .iter()
+                    .find_map(|(entity, iid)| (iid == liid).then_some(entity))
+                else {
+                    continue;
+                };
+                let level_cells = cells
+                    .iter()
+                    .filter(|data| {
+                        parents
+                            .get(data.layer.parent())
+                            .is_ok_and(|&ChildOf(level)| level == level_entity)
+                    })
+                    .filter_map(|data| Some((*data.coords, data.cell()?)));
+                level_grid.insert_level(level_entity, liid, level_cells);
+            }
+            LevelEvent::Despawned(liid) => level_grid.remove_level(liid),
+            _ => {}
+        }
+    }
+}
+
+fn clear_level_grid(mut level_grid: ResMut<LevelGrid>) {
+    level_grid.clear();
+}
+
+/// Start a [LevelTransition] when the player enters a neighbour level
 fn update_level_based_on_player_pos(
+    mut commands: Commands,
     levels: Query<(&LevelIid, &Transform), Without<Player>>,
-    players: Query<&Transform, With<Player>>,
+    mut players: Query<&mut Transform, With<Player>>,
     ldtk_projects: Query<&LdtkProjectHandle>,
     mut level_selection: ResMut<LevelSelection>,
     ldtk_project_assets: Res<Assets<LdtkProject>>,
+    settings: Res<LevelTransitionSettings>,
+    mut in_game_state: ResMut<NextState<InGameState>>,
 ) -> Result {
     let ldtk_project = ldtk_project_assets
         .get(ldtk_projects.single()?)
         .ok_or("Project should be loaded if level has spawned")?;
 
-    let player_pos = players.single()?.translation.xy();
+    let mut player_transform = players.single_mut()?;
+    let player_pos = player_transform.translation.xy();
 
-    levels
+    let level_bounds = levels
         .iter()
         // Get level bounds
         .filter_map(|(liid, transform)| {
@@ -215,18 +314,83 @@ fn update_level_based_on_player_pos(
             Some((level, Rect { min, max }))
         })
         // Check if player is in level
-        .filter_map(|(level, level_bounds)| level_bounds.contains(player_pos).then_some(level))
-        // Select level
-        .for_each(|level| *level_selection = LevelSelection::iid(level.iid.clone()));
+        .filter(|(_level, level_bounds)| level_bounds.contains(player_pos))
+        .collect::<Vec<_>>();
+    if level_bounds
+        .iter()
+        .any(|(level, _)| level_selection.is_match(&LevelIndices::default(), level))
+    {
+        // Still in the current level
+        return Ok(());
+    }
+    if let Some((level, bounds)) = level_bounds.first() {
+        *level_selection = LevelSelection::iid(level.iid.clone());
+        // Put the player fully inside the new level
+        let inside = bounds.inflate(-1.);
+        let inside = Rect {
+            min: inside.min + PLAYER_HALF_SIZE,
+            max: inside.max - PLAYER_HALF_SIZE,
+        };
+        let pos = player_pos.clamp(inside.min, inside.max.max(inside.min));
+        player_transform.translation = pos.extend(player_transform.translation.z);
+
+        commands.insert_resource(LevelTransition::new(&settings));
+        in_game_state.set(InGameState::LevelTransition);
+    }
     Ok(())
 }
 
+/// Black screen of a [LevelTransitionKind::Fade]
+#[derive(Component)]
+struct TransitionFader;
+
+fn start_level_transition(mut commands: Commands, transition: Res<LevelTransition>) {
+    if transition.kind == LevelTransitionKind::Fade {
+        let half_secs = transition.timer.duration().as_secs_f32() / 2.;
+        commands.spawn((
+            fader(Color::NONE, Color::BLACK, half_secs),
+            TransitionFader,
+            StateScoped(InGameState::LevelTransition),
+        ));
+    }
+}
+
+fn tick_level_transition(
+    mut commands: Commands,
+    mut transition: ResMut<LevelTransition>,
+    faders: Query<Entity, With<TransitionFader>>,
+    mut in_game_state: ResMut<NextState<InGameState>>,
+    time: Res<Time>,
+) {
+    let half_secs = transition.timer.duration().as_secs_f32() / 2.;
+    let was_fading_out = transition.timer.elapsed_secs() < half_secs;
+    transition.timer.tick(time.delta());
+    if transition.kind == LevelTransitionKind::Fade
+        && was_fading_out
+        && transition.timer.elapsed_secs() >= half_secs
+    {
+        // Fade in the new level
+        for entity in &faders {
+            commands.entity(entity).despawn();
+        }
+        commands.spawn((
+            fader(Color::BLACK, Color::NONE, half_secs),
+            TransitionFader,
+            StateScoped(InGameState::LevelTransition),
+        ));
+    }
+    if transition.timer.finished() {
+        commands.remove_resource::<LevelTransition>();
+        in_game_state.set(InGameState::Running);
+    }
+}
+
 fn restart_level(
     mut commands: Commands,
     level_query: Query<Entity, With<LevelIid>>,
-    input: Res<ButtonInput<KeyCode>>,
+    input: Res<GameInput>,
 ) {
-    if input.just_pressed(KeyCode::KeyR) {
+    if input.just_pressed(GameKey::Restart) {
         for level_entity in &level_query {
             commands.entity(level_entity).insert(Respawn);
         }
@@ -235,22 +399,23 @@ fn restart_level(
 
 fn open_door(
     mut commands: Commands,
-    mut collisions: EventReader<CollisionEvent>,
-    mut players: Query<(Entity, &mut Items), With<Player>>,
+    mut collisions: Collisions<With<Player>, With<Door>>,
+    mut players: Query<&mut Items, With<Player>>,
     doors: Query<&Items, (With<Door>, Without<Player>)>,
     assets: Res<ItemAssets>,
 ) {
-    let (player_entity, mut player_items) = players.single_mut().expect("Player");
+    let mut player_items = players.single_mut().expect("Player");
     collisions
-        .read()
-        .filter_map(start_event_filter)
-        .filter_map(|(&e1, &e2)| doors.get_either(e1, e2))
-        .filter(|(_expected_items, _door_entity, other_entity)| player_entity == *other_entity)
-        .for_each(|(expected_items, door_entity, _player_entity)| {
+        .started()
+        .filter_map(|(_player_entity, door_entity)| {
+            Some((doors.get(door_entity).ok()?, door_entity))
+        })
+        .for_each(|(expected_items, door_entity)| {
             if player_items.contains_items(expected_items) {
                 info!("Player open door");
-                player_items.remove_items(expected_items);
+                player_items.use_items(expected_items);
                 commands.entity(door_entity).despawn();
+                commands.trigger(ImpactEvent::DoorSlam);
             } else {
                 // Show a popup that shows the expected items to open the door
                 let images = expected_items
@@ -267,71 +432,110 @@ fn open_door(
 }
 
 fn end_level(
-    mut collisions: EventReader<CollisionEvent>,
-    mut players: Query<Entity, With<Player>>,
-    end_levels: Query<&EndLevel>,
+    mut collisions: Collisions<With<Player>, With<EndLevel>>,
     mut in_game_state: ResMut<NextState<InGameState>>,
 ) {
-    let player_entity = players.single_mut().expect("Player");
-    collisions
-        .read()
-        .filter_map(start_event_filter)
-        .filter_map(|(&e1, &e2)| end_levels.get_either(e1, e2))
-        .filter(|(_, _end_entity, other_entity)| player_entity == *other_entity)
-        .for_each(|(_, _end_entity, _player_entity)| {
-            info!("Player end level");
-            in_game_state.set(InGameState::PlayerEndedLevel);
-        });
+    if collisions.started().count() > 0 {
+        info!("Player end level");
+        in_game_state.set(InGameState::PlayerEndedLevel);
+    }
 }
 
 fn on_dig(
     trigger: Trigger<DigEvent>,
     mut commands: Commands,
-    mut cells: Query<&mut TileVisible, With<Destructible>>,
+    mut cells: Query<(&mut TileVisible, &GridCoords, &ChildOf), With<Destructible>>,
+    parents: Query<&ChildOf, Without<ColliderCell>>,
+    mut level_grid: ResMut<LevelGrid>,
 ) {
-    warn!("DigEvent {}", trigger.target());
-    if let Ok(mut visible) = cells.get_mut(trigger.target()) {
+    if let Ok((mut visible, &coords, &ChildOf(layer))) = cells.get_mut(trigger.target()) {
         visible.0 = false;
         commands.entity(trigger.target()).insert(Destroyed);
-        commands.trigger(UpdateCollidersEvent);
+        if let Ok(&ChildOf(level)) = parents.get(layer) {
+            level_grid.set_destroyed(level, coords, true);
+            commands.trigger_targets(
+                UpdateCollidersEvent {
+                    cells: vec![coords],
+                },
+                level,
+            );
+        }
     }
 }
 
+/// Only re-merge the [LevelCollider]s of the level touched by the changed cells,
+/// so other colliders, and the bodies resting on them, are left alone
 fn recalculate_level_collisions(
-    _trigger: Trigger<UpdateCollidersEvent>,
+    trigger: Trigger<UpdateCollidersEvent>,
     mut commands: Commands,
-    colliders: Query<Entity, With<LevelCollider>>,
+    colliders: Query<(Entity, &LevelCollider, &ChildOf)>,
     collider_cells: Query<(&GridCoords, &ChildOf), (With<ColliderCell>, Without<Destroyed>)>,
     parents: Query<&ChildOf, Without<ColliderCell>>,
     levels: Query<LevelData>,
     ldtk_projects: Query<&LdtkProjectHandle>,
     ldtk_project_assets: Res<Assets<LdtkProject>>,
 ) -> Result {
-    warn!("recalculate_level_collisions");
+    let level_entity = trigger.target();
+    let changed_cells = &trigger.cells;
 
-    let existing_colliders = colliders.iter().collect::<Vec<_>>();
+    let touched_colliders = colliders
+        .iter()
+        .filter(|(_, _, &ChildOf(level))| level == level_entity)
+        .filter(|(_, LevelCollider(rect), _)| {
+            changed_cells
+                .iter()
+                .any(|cell| rect.contains(ivec2(cell.x, cell.y)))
+        })
+        .map(|(entity, LevelCollider(rect), _)| (entity, *rect))
+        .collect::<Vec<_>>();
+
+    // The area to re-merge: the touched colliders and the changed cells
+    let area = touched_colliders
+        .iter()
+        .map(|(_, rect)| *rect)
+        .chain(
+            changed_cells
+                .iter()
+                .map(|cell| IRect::new(cell.x, cell.y, cell.x, cell.y)),
+        )
+        .reduce(|area, rect| area.union(rect));
+    let Some(area) = area else {
+        return Ok(());
+    };
+    let in_area = |cell: &GridCoords| {
+        changed_cells.contains(cell)
+            || touched_colliders
+                .iter()
+                .any(|(_, rect)| rect.contains(ivec2(cell.x, cell.y)))
+    };
+
+    // Merge the remaining cells of the area, relative to it
+    let cells = collider_cells
+        .iter()
+        .filter(|(_, &ChildOf(layer))| {
+            parents
+                .get(layer)
+                .is_ok_and(|&ChildOf(level)| level == level_entity)
+        })
+        .map(|(cell, _)| *cell)
+        .filter(in_area)
+        .map(|cell| GridCoords::new(cell.x - area.min.x, cell.y - area.min.y))
+        .collect::<HashSet<_>>();
+    let size = area.size() + IVec2::ONE;
 
     let ldtk_project = ldtk_project_assets
         .get(ldtk_projects.single()?)
         .ok_or("Project should be loaded if level has spawned")?
         .as_standalone();
+    let level = levels.get(level_entity)?.level(ldtk_project)?;
+    let grid_size = level.collision_layer()?.grid_size;
 
-    let level_colliders = LevelColliders::new(collider_cells, &parents);
-    for level_data in &levels {
-        let level = level_data.level(ldtk_project)?;
-        let layer = level.collision_layer()?;
-        for rect in level_colliders.rectangles(&level_data.entity, layer.c_wid, layer.c_hei) {
-            commands.spawn((
-                level_collider(rect, layer.grid_size),
-                ChildOf(level_data.entity),
-            ));
-        }
+    for rect in grid_rectangles(&cells, size.x, size.y) {
+        let rect = IRect::from_corners(rect.min + area.min, rect.max + area.min);
+        commands.spawn((level_collider(rect, grid_size), ChildOf(level_entity)));
     }
-
-    // remove old colliders
-    for e in existing_colliders {
-        commands.entity(e).despawn();
+    for (entity, _) in touched_colliders {
+        commands.entity(entity).despawn();
     }
-
     Ok(())
 }
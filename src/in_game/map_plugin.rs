@@ -0,0 +1,362 @@
+use crate::{
+    components::{
+        despawn_all,
+        item::Chest,
+        level::{Door, EndLevel, COLLISIONS_LAYER},
+        level_grid::LevelGrid,
+        map::{map_image, ShowWorldMapEvent, VisitedLevels},
+        player::Player,
+    },
+    schedule::{GameState, InGameState},
+    theme::widget,
+};
+use bevy::{ecs::spawn::SpawnIter, prelude::*};
+use bevy_ecs_ldtk::{ldtk::Level, prelude::*};
+
+/// Width of the minimap, in pixels
+const MINIMAP_WIDTH: f32 = 200.;
+
+pub fn map_plugin(app: &mut App) {
+    app.register_type::<VisitedLevels>()
+        .init_resource::<VisitedLevels>()
+        .add_systems(OnEnter(GameState::InGame), spawn_minimap)
+        .add_systems(
+            OnExit(GameState::InGame),
+            (despawn_all::<Minimap>, reset_visited_levels),
+        )
+        .add_systems(
+            Update,
+            (
+                visit_level,
+                draw_minimap,
+                add_minimap_markers,
+                move_minimap_markers.after(draw_minimap),
+            )
+                .run_if(in_state(GameState::InGame)),
+        )
+        .add_systems(OnExit(InGameState::Pause), despawn_all::<WorldMap>)
+        .add_observer(show_world_map);
+}
+
+/// HUD panel showing the cells of the current level
+#[derive(Component)]
+struct Minimap {
+    image: Handle<Image>,
+    /// Level drawn on the minimap
+    level: Option<Entity>,
+    /// Bounds of the drawn level, in world coordinates
+    bounds: Rect,
+}
+
+/// Entity shown on the [Minimap]
+#[derive(Component)]
+struct MinimapMarker(Entity);
+
+/// Full-screen map of the levels of the current world
+#[derive(Component)]
+struct WorldMap;
+
+fn spawn_minimap(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = images.add(Image::default());
+    commands.spawn((
+        Name::new("Minimap"),
+        Minimap {
+            image: image.clone(),
+            level: None,
+            bounds: Rect::default(),
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            right: Val::Px(10.0),
+            width: Val::Px(MINIMAP_WIDTH),
+            height: Val::Px(MINIMAP_WIDTH / 2.),
+            border: UiRect::all(Val::Px(1.0)),
+            ..Default::default()
+        },
+        ImageNode::new(image),
+        BackgroundColor(Color::srgba(0., 0., 0., 0.5)),
+        BorderColor(Color::srgba(0.8, 0.8, 0.8, 0.5)),
+    ));
+}
+
+fn reset_visited_levels(mut visited_levels: ResMut<VisitedLevels>) {
+    visited_levels.0.clear();
+}
+
+/// Remember the levels the player has been in
+fn visit_level(
+    mut visited_levels: ResMut<VisitedLevels>,
+    ldtk_projects: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    level_selection: Res<LevelSelection>,
+) {
+    let Some(level) = ldtk_projects
+        .single()
+        .ok()
+        .and_then(|handle| ldtk_project_assets.get(handle))
+        .and_then(|ldtk_project| {
+            ldtk_project
+                .iter_raw_levels()
+                .find(|level| level_selection.is_match(&LevelIndices::default(), level))
+        })
+    else {
+        return;
+    };
+    if !visited_levels.0.contains(&level.iid) {
+        visited_levels.0.insert(level.iid.clone());
+    }
+}
+
+/// Draw the collision cells of the current level, one pixel per cell,
+/// when the level changes or a cell is dug
+fn draw_minimap(
+    mut minimaps: Query<(&mut Minimap, &mut Node)>,
+    levels: Query<(Entity, &Transform, &LevelIid)>,
+    ldtk_projects: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    level_selection: Res<LevelSelection>,
+    level_grid: Res<LevelGrid>,
+    mut images: ResMut<Assets<Image>>,
+) -> Result {
+    let Ok((mut minimap, mut node)) = minimaps.single_mut() else {
+        return Ok(());
+    };
+    let Some(ldtk_project) = ldtk_projects
+        .single()
+        .ok()
+        .and_then(|handle| ldtk_project_assets.get(handle))
+    else {
+        return Ok(());
+    };
+    let Some((level_entity, level_transform, level)) =
+        levels.iter().find_map(|(entity, transform, iid)| {
+            let level = ldtk_project.get_raw_level_by_iid(&iid.to_string())?;
+            level_selection
+                .is_match(&LevelIndices::default(), level)
+                .then_some((entity, transform, level))
+        })
+    else {
+        return Ok(());
+    };
+    if minimap.level == Some(level_entity) && !level_grid.is_changed() {
+        return Ok(());
+    }
+
+    let layer = level
+        .layer_instances
+        .as_ref()
+        .and_then(|layers| layers.get(COLLISIONS_LAYER))
+        .ok_or("Missing Collision")?;
+    let size = UVec2::new(layer.c_wid as u32, layer.c_hei as u32);
+    // Grid coordinates go up, image rows go down
+    let pixels = level_grid
+        .cells(level_entity)
+        .filter(|(_, cell)| !cell.destroyed)
+        .map(|(coords, cell)| {
+            (
+                IVec2::new(coords.x, size.y as i32 - 1 - coords.y),
+                cell.kind.map_color(),
+            )
+        });
+    let image = map_image(size, pixels)?;
+    images.insert(&minimap.image, image);
+
+    node.height = Val::Px(MINIMAP_WIDTH * size.y as f32 / size.x as f32);
+    let origin = level_transform.translation.xy();
+    minimap.level = Some(level_entity);
+    minimap.bounds = Rect::from_corners(
+        origin,
+        origin + Vec2::new(level.px_wid as f32, level.px_hei as f32),
+    );
+    Ok(())
+}
+
+/// Add a marker on the [Minimap] for the player, the exit, the doors and the chests
+fn add_minimap_markers(
+    mut commands: Commands,
+    minimaps: Query<Entity, With<Minimap>>,
+    markers: Query<&MinimapMarker>,
+    targets: Query<
+        (Entity, Has<Player>, Has<Door>, Has<Chest>),
+        Or<(With<Player>, With<Door>, With<Chest>, With<EndLevel>)>,
+    >,
+) {
+    let Ok(minimap) = minimaps.single() else {
+        return;
+    };
+    for (target, player, door, chest) in &targets {
+        if markers.iter().any(|marker| marker.0 == target) {
+            continue;
+        }
+        let (color, size) = match (player, door, chest) {
+            (true, _, _) => (Color::WHITE, 5.),
+            (_, true, _) => (Color::srgb(1., 0.5, 0.1), 4.),
+            (_, _, true) => (Color::srgb(1., 0.9, 0.2), 4.),
+            // The exit
+            _ => (Color::srgb(0.2, 1., 0.3), 5.),
+        };
+        commands.spawn((
+            Name::new("MinimapMarker"),
+            MinimapMarker(target),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(size),
+                height: Val::Px(size),
+                margin: UiRect::all(Val::Px(-size / 2.)),
+                ..Default::default()
+            },
+            BackgroundColor(color),
+            Visibility::Hidden,
+            ChildOf(minimap),
+        ));
+    }
+}
+
+/// Place the markers over their entity, hiding the ones out of the drawn level
+fn move_minimap_markers(
+    mut commands: Commands,
+    minimaps: Query<&Minimap>,
+    mut markers: Query<(Entity, &MinimapMarker, &mut Node, &mut Visibility)>,
+    targets: Query<&GlobalTransform>,
+) {
+    let Ok(minimap) = minimaps.single() else {
+        return;
+    };
+    for (entity, marker, mut node, mut visibility) in &mut markers {
+        let Ok(target_transform) = targets.get(marker.0) else {
+            // Opened chest or door
+            commands.entity(entity).despawn();
+            continue;
+        };
+        let position = target_transform.translation().xy();
+        if minimap.level.is_none() || !minimap.bounds.contains(position) {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        let fraction = (position - minimap.bounds.min) / minimap.bounds.size();
+        node.left = Val::Percent(fraction.x * 100.);
+        node.bottom = Val::Percent(fraction.y * 100.);
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
+
+/// A level on the [WorldMap], hidden by fog if it was never visited
+fn world_map_level(level: &Level, world_bounds: Rect, visited: bool, current: bool) -> impl Bundle {
+    let min = (Vec2::new(level.world_x as f32, level.world_y as f32) - world_bounds.min)
+        / world_bounds.size();
+    let size = Vec2::new(level.px_wid as f32, level.px_hei as f32) / world_bounds.size();
+    let (color, text) = if current {
+        (Color::srgb(0.867, 0.827, 0.412), level.identifier.clone())
+    } else if visited {
+        (Color::srgb(0.45, 0.55, 0.6), level.identifier.clone())
+    } else {
+        (Color::srgba(0.1, 0.1, 0.1, 0.9), "?".to_string())
+    };
+    (
+        Name::new(format!("WorldMapLevel {}", level.identifier)),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(min.x * 100.),
+            top: Val::Percent(min.y * 100.),
+            width: Val::Percent(size.x * 100.),
+            height: Val::Percent(size.y * 100.),
+            border: UiRect::all(Val::Px(1.0)),
+            ..widget::centered()
+        },
+        BackgroundColor(color),
+        BorderColor(Color::BLACK),
+        children![(
+            Text(text),
+            TextFont::from_font_size(14.),
+            TextColor(Color::BLACK)
+        )],
+    )
+}
+
+fn show_world_map(
+    _trigger: Trigger<ShowWorldMapEvent>,
+    mut commands: Commands,
+    ldtk_projects: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    level_selection: Res<LevelSelection>,
+    visited_levels: Res<VisitedLevels>,
+) -> Result {
+    let ldtk_project = ldtk_project_assets
+        .get(ldtk_projects.single()?)
+        .ok_or("Project should be loaded in game")?;
+    let is_current = |level: &Level| level_selection.is_match(&LevelIndices::default(), level);
+
+    // Levels of the world the player is in
+    let json = ldtk_project.json_data();
+    let levels = if json.worlds.is_empty() {
+        &json.levels
+    } else {
+        json.worlds
+            .iter()
+            .map(|world| &world.levels)
+            .find(|levels| levels.iter().any(is_current))
+            .ok_or("Current level should be in a world")?
+    };
+    let world_bounds = levels
+        .iter()
+        .map(|level| {
+            let min = Vec2::new(level.world_x as f32, level.world_y as f32);
+            Rect::from_corners(
+                min,
+                min + Vec2::new(level.px_wid as f32, level.px_hei as f32),
+            )
+        })
+        .reduce(|bounds, level_bounds| bounds.union(level_bounds))
+        .ok_or("World should have levels")?;
+    let level_nodes = levels
+        .iter()
+        .map(|level| {
+            world_map_level(
+                level,
+                world_bounds,
+                visited_levels.0.contains(&level.iid),
+                is_current(level),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    commands.spawn((
+        WorldMap,
+        Name::new("WorldMap"),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..widget::vsizer()
+        },
+        BackgroundColor(Color::srgba(0., 0., 0., 0.85)),
+        GlobalZIndex(4),
+        children![
+            widget::popup_title("Map"),
+            (
+                Name::new("WorldMapLevels"),
+                Node {
+                    height: Val::Percent(70.0),
+                    max_width: Val::Percent(90.0),
+                    aspect_ratio: Some(world_bounds.width() / world_bounds.height()),
+                    margin: UiRect::all(Val::Px(20.0)),
+                    ..Default::default()
+                },
+                Children::spawn(SpawnIter(level_nodes.into_iter())),
+            ),
+            widget::menu_button("Back", on_close_world_map),
+        ],
+    ));
+    Ok(())
+}
+
+fn on_close_world_map(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    world_maps: Query<Entity, With<WorldMap>>,
+) {
+    for world_map in &world_maps {
+        commands.entity(world_map).despawn();
+    }
+}
@@ -1,13 +1,24 @@
+mod ability_plugin;
+mod animation_plugin;
+mod attack_plugin;
 mod character_plugin;
 mod death_menu;
 mod end_level_menu;
 mod enemy_plugin;
+mod ghost_plugin;
 mod hud_plugin;
 mod item_plugin;
+mod juice_plugin;
 mod level_plugin;
+mod map_plugin;
+mod particles_plugin;
 mod pause_menu;
 mod player_plugin;
 mod popup_with_images;
+mod progress_plugin;
+mod replay_plugin;
+mod score_plugin;
+mod speedrun_plugin;
 
 pub use plugin::InGamePlugins;
 
@@ -29,15 +40,27 @@ mod plugin {
         fn build(self) -> PluginGroupBuilder {
             PluginGroupBuilder::start::<Self>()
                 .add(death_menu::plugin)
+                .add(ability_plugin::ability_plugin)
+                // Before the plugins loading clips
+                .add(animation_plugin::animation_plugin)
+                .add(attack_plugin::attack_plugin)
                 .add(character_plugin::character_plugin)
                 .add(enemy_plugin::enemy_plugin)
+                .add(ghost_plugin::ghost_plugin)
                 .add(hud_plugin::hud_plugin)
                 .add(level_plugin::level_plugin)
+                .add(map_plugin::map_plugin)
                 .add(player_plugin::player_plugin)
                 .add(pause_menu::pause_menu_plugin)
                 .add(item_plugin::item_plugin)
+                .add(juice_plugin::juice_plugin)
+                .add(particles_plugin::particles_plugin)
                 .add(end_level_menu::end_level_menu_plugin)
                 .add(popup_with_images::popup_with_images_plugin)
+                .add(progress_plugin::progress_plugin)
+                .add(replay_plugin::replay_plugin)
+                .add(score_plugin::score_plugin)
+                .add(speedrun_plugin::speedrun_plugin)
                 .add(in_game_plugin)
         }
     }
@@ -52,13 +75,17 @@ mod plugin {
             .add_systems(OnExit(InGameState::Pause), unpause)
             .add_systems(OnEnter(InGameState::ShowPopup), pause)
             .add_systems(OnExit(InGameState::ShowPopup), unpause)
-            .add_systems(Update, switch_to_pause.in_set(InGameSet::UserInput))
             .add_systems(
                 Update,
-                (
-                    enter_popup_state.in_set(InGameSet::EntityUpdate),
-                    exit_popup_state.run_if(in_state(InGameState::ShowPopup)),
-                ),
+                switch_to_pause.run_if(in_state(InGameState::Running)),
+            )
+            .add_systems(
+                FixedUpdate,
+                enter_popup_state.in_set(InGameSet::EntityUpdate),
+            )
+            .add_systems(
+                Update,
+                exit_popup_state.run_if(in_state(InGameState::ShowPopup)),
             );
     }
 
@@ -0,0 +1,161 @@
+use crate::{
+    asset_tracking::LoadResource,
+    components::{
+        character::InWater,
+        despawn_all,
+        enemy::EnemyDeathEvent,
+        item::ChestOpenedEvent,
+        particles::{
+            Particle, ParticleAssets, ParticleEmitEvent, ParticlePresets, ParticlePresetsLoader,
+        },
+        player::{DigEvent, Player},
+    },
+    schedule::{GameState, InGameSet},
+};
+use bevy::prelude::*;
+
+/// Particles are drawn over the level and the characters
+const PARTICLE_Z: f32 = 20.;
+
+pub fn particles_plugin(app: &mut App) {
+    app.init_asset::<ParticlePresets>()
+        .init_asset_loader::<ParticlePresetsLoader>()
+        .load_resource::<ParticleAssets>()
+        .add_systems(FixedUpdate, move_particles.in_set(InGameSet::EntityUpdate))
+        .add_systems(
+            FixedUpdate,
+            splash_water.in_set(InGameSet::CollisionDetection),
+        )
+        .add_systems(OnExit(GameState::InGame), despawn_all::<Particle>)
+        .add_observer(emit_particles)
+        .add_observer(dig_particles)
+        .add_observer(enemy_death_particles)
+        .add_observer(chest_particles);
+}
+
+/// Pseudo random number in `0..1`, good enough for cosmetic effects
+fn noise(seed: u32) -> f32 {
+    (seed.wrapping_mul(2_654_435_761) >> 8) as f32 / (1 << 24) as f32
+}
+
+fn emit_particles(
+    trigger: Trigger<ParticleEmitEvent>,
+    mut commands: Commands,
+    particle_assets: Res<ParticleAssets>,
+    presets: Res<Assets<ParticlePresets>>,
+) {
+    let event = trigger.event();
+    let Some(preset) = presets
+        .get(&particle_assets.presets)
+        .and_then(|presets| presets.get(event.preset))
+    else {
+        warn!("Unknown particle preset {}", event.preset);
+        return;
+    };
+    let (r, g, b, a) = preset.start_color;
+    let start_color = LinearRgba::new(r, g, b, a);
+    let (r, g, b, a) = preset.end_color;
+    let end_color = LinearRgba::new(r, g, b, a);
+
+    let seed = event.position.x.to_bits() ^ event.position.y.to_bits().rotate_left(16);
+    for i in 0..preset.count {
+        // Spread evenly over the angle, with some jitter
+        let jitter = noise(seed.wrapping_add(i * 2));
+        let fraction = (i as f32 + jitter) / preset.count as f32;
+        let angle = (preset.direction + preset.spread * (fraction - 0.5)).to_radians();
+        let speed = preset.speed.0
+            + (preset.speed.1 - preset.speed.0) * noise(seed.wrapping_add(i * 2 + 1));
+        commands.spawn((
+            Particle {
+                velocity: Vec2::from_angle(angle) * speed,
+                gravity: preset.gravity,
+                age: 0.,
+                lifetime: preset.lifetime,
+                start_color,
+                end_color,
+            },
+            Sprite::from_color(start_color, Vec2::splat(preset.size)),
+            Transform::from_translation(event.position.extend(PARTICLE_Z)),
+        ));
+    }
+}
+
+/// Particles age with the game, so they stay while it is paused, and despawn at the end
+/// of their lifetime
+fn move_particles(
+    mut commands: Commands,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    for (entity, mut particle, mut transform, mut sprite) in &mut particles {
+        particle.age += delta;
+        if particle.age >= particle.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        particle.velocity.y -= particle.gravity * delta;
+        transform.translation += (particle.velocity * delta).extend(0.);
+        sprite.color = particle.color();
+    }
+}
+
+/// Position of the entity targeted by a trigger, if it still has one
+fn target_position(transforms: &Query<&GlobalTransform>, target: Entity) -> Option<Vec2> {
+    transforms
+        .get(target)
+        .ok()
+        .map(|transform| transform.translation().xy())
+}
+
+fn dig_particles(
+    trigger: Trigger<DigEvent>,
+    mut commands: Commands,
+    transforms: Query<&GlobalTransform>,
+) {
+    if let Some(position) = target_position(&transforms, trigger.target()) {
+        commands.trigger(ParticleEmitEvent::new("dig", position));
+    }
+}
+
+fn enemy_death_particles(
+    trigger: Trigger<EnemyDeathEvent>,
+    mut commands: Commands,
+    transforms: Query<&GlobalTransform>,
+) {
+    if let Some(position) = target_position(&transforms, trigger.target()) {
+        commands.trigger(ParticleEmitEvent::new("enemy_death", position));
+    }
+}
+
+/// Sparkles on the opened chest, and on the player getting its items
+fn chest_particles(
+    trigger: Trigger<ChestOpenedEvent>,
+    mut commands: Commands,
+    transforms: Query<&GlobalTransform>,
+    players: Query<&GlobalTransform, With<Player>>,
+) {
+    if let Some(position) = target_position(&transforms, trigger.target()) {
+        commands.trigger(ParticleEmitEvent::new("chest_open", position));
+    }
+    if let Ok(player_transform) = players.single() {
+        commands.trigger(ParticleEmitEvent::new(
+            "item_pickup",
+            player_transform.translation().xy(),
+        ));
+    }
+}
+
+/// Splash when a character enters or leaves water
+fn splash_water(mut commands: Commands, characters: Query<(Ref<InWater>, &Transform)>) {
+    for (in_water, transform) in &characters {
+        if in_water.is_changed() && !in_water.is_added() {
+            let preset = if **in_water {
+                "splash_in"
+            } else {
+                "splash_out"
+            };
+            commands.trigger(ParticleEmitEvent::new(preset, transform.translation.xy()));
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
-    components::despawn_all,
+    components::{despawn_all, map::ShowWorldMapEvent},
     schedule::{GameState, InGameState},
+    settings::ShowSettingsEvent,
     theme::widget,
 };
 use bevy::prelude::*;
@@ -23,6 +24,8 @@ fn pause_menu() -> impl Bundle {
         children![
             widget::popup_title("Pause"),
             widget::menu_button("Back to game", on_back_to_game),
+            widget::menu_button("Map", on_show_map),
+            widget::menu_button("Settings", on_settings),
             widget::menu_button("Quit game", on_quit_game)
         ],
     )
@@ -35,6 +38,14 @@ fn on_back_to_game(_trigger: Trigger<Pointer<Click>>, mut state: ResMut<NextStat
     state.set(InGameState::Running);
 }
 
+fn on_show_map(_trigger: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(ShowWorldMapEvent);
+}
+
+fn on_settings(_trigger: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(ShowSettingsEvent);
+}
+
 fn on_quit_game(_trigger: Trigger<Pointer<Click>>, mut state: ResMut<NextState<GameState>>) {
     state.set(GameState::Menu);
 }
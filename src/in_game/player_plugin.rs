@@ -1,22 +1,26 @@
 use crate::{
     asset_tracking::LoadResource,
     components::{
+        ability::{Ability, AirJump, Dashing, WallJumping},
+        animation::{SpriteAnimation, SpriteClips},
+        attack::Attacking,
         character::{
-            ground_sensor, AnimationTimer, Climber, Damage, Dying, GroundDetection, GroundSensor,
-            InWater, JumpSpeed, Jumping, Life, Movement, Speed,
+            ground_sensor, wall_sensor, Climber, Damage, Dying, Facing, GroundDetection,
+            GroundSensor, HitStun, InWater, JumpControl, JumpSpeed, JumpTimings, Jumping, Life,
+            Movement, Speed, WallDetection,
         },
-        enemy::Enemy,
-        item::{Item, Items},
-        level::{Destructible, COLLISIONS_LAYER},
-        player::{DigEvent, Player, PlayerAssets, PlayerDeathEvent},
+        enemy::{Enemy, EnemyDeathEvent},
+        input::{GameInput, GameKey},
+        item::Items,
+        juice::ImpactEvent,
+        level::COLLISIONS_LAYER,
+        level_grid::LevelGrid,
+        player::{DigEvent, Player, PlayerAssets, PLAYER_DEATH_CLIP},
         GROUP_ENEMY,
     },
+    in_game::animation_plugin::animate_sprites,
     schedule::InGameSet,
-    utils::{
-        collisions::{start_event_filter, QueryEither},
-        invulnerable::Invulnerable,
-        iter_ext::IterExt,
-    },
+    utils::{collisions::Collisions, invulnerable::Invulnerable, iter_ext::IterExt},
 };
 use bevy::prelude::*;
 use bevy_ecs_ldtk::{prelude::*, utils::translation_to_grid_coords};
@@ -25,34 +29,46 @@ use std::time::Duration;
 
 pub fn player_plugin(app: &mut App) {
     app.load_resource::<PlayerAssets>()
-        .add_systems(Update, (movement, dig_hole).in_set(InGameSet::UserInput))
         .add_systems(
-            Update,
-            (
-                (animate_walk, animate_jump).after(tick_and_update_sprite),
-                animate_death,
-            )
+            FixedUpdate,
+            (movement, dig_hole).in_set(InGameSet::UserInput),
+        )
+        .add_systems(
+            FixedUpdate,
+            (select_player_clip.before(animate_sprites), tick_hit_stun)
                 .in_set(InGameSet::EntityUpdate),
         )
         .add_systems(
-            Update,
+            FixedUpdate,
             (enemy_hit_player, player_hits_enemy).in_set(InGameSet::CollisionDetection),
         )
         .add_observer(init_player_sprite)
         .add_observer(spawn_ground_sensor)
-        .add_observer(player_dying);
+        .add_observer(spawn_wall_sensors);
 }
 
 fn init_player_sprite(
     trigger: Trigger<OnAdd, Player>,
+    mut commands: Commands,
     mut players: Query<&mut Sprite, With<Player>>,
     assets: Res<PlayerAssets>,
+    sprite_clips: Res<Assets<SpriteClips>>,
 ) {
     if let Ok(mut sprite) = players.get_mut(trigger.target()) {
-        sprite.texture_atlas = Some(TextureAtlas {
-            layout: assets.walk_atlas_layout.clone(),
-            index: 16,
-        });
+        // Don't show the whole sheet until the animation runs
+        if let Some(clip) = sprite_clips
+            .get(&assets.animations)
+            .and_then(|clips| clips.get("idle"))
+        {
+            sprite.image = clip.image.clone();
+            sprite.texture_atlas = Some(TextureAtlas {
+                layout: clip.layout.clone(),
+                index: clip.frames[0],
+            });
+        }
+        commands
+            .entity(trigger.target())
+            .insert(SpriteAnimation::new(assets.animations.clone(), "idle"));
     }
 }
 
@@ -61,202 +77,163 @@ fn spawn_ground_sensor(trigger: Trigger<OnAdd, GroundDetection>, mut commands: C
     commands.spawn(ground_sensor(trigger.target(), Vec2::new(7.0, 8.0)));
 }
 
-fn next_sprite_index_option(indices: &[usize], current: usize) -> Option<usize> {
-    let i = indices.iter().position(|&v| v == current)?;
-    Some((i + 1) % indices.len())
-}
-
-fn next_sprite_index_repeat(indices: &[usize], current: usize) -> usize {
-    let idx = next_sprite_index_option(indices, current).unwrap_or(0);
-    indices[idx]
-}
-
-fn next_sprite_index_once(indices: &[usize], current: usize) -> usize {
-    next_sprite_index_option(indices, current)
-        .map(|i| indices[i])
-        .unwrap_or(current)
-}
-
-fn tick_and_update_sprite(
-    mut players: Query<
-        (&Jumping, &mut Sprite, &mut AnimationTimer),
-        (With<Player>, Without<Dying>),
-    >,
-    assets: Res<PlayerAssets>,
-    time: Res<Time>,
-) {
-    if let Ok((&jumping, mut sprite, mut timer)) = players.single_mut() {
-        timer.tick(time.delta());
-        if *jumping {
-            sprite.image = assets.jump_sprites.clone();
-            sprite.texture_atlas = Some(TextureAtlas {
-                layout: assets.jump_atlas_layout.clone(),
-                index: 0,
-            });
-        } else {
-            sprite.image = assets.walk_sprites.clone();
-            sprite.texture_atlas = Some(TextureAtlas {
-                layout: assets.walk_atlas_layout.clone(),
-                index: 0,
-            });
-        }
+/// Spawn a [Sensor] on each side of a collider to detect when it touches a wall
+fn spawn_wall_sensors(trigger: Trigger<OnAdd, WallDetection>, mut commands: Commands) {
+    for side in [Facing::Left, Facing::Right] {
+        commands.spawn(wall_sensor(trigger.target(), Vec2::new(5.0, 7.0), side));
     }
 }
 
-fn animate_walk(
+/// Select the clip of the player animation from its state
+fn select_player_clip(
     mut players: Query<
-        (&Velocity, &Climber, &Jumping, &AnimationTimer, &mut Sprite),
-        (With<Player>, Without<Dying>),
-    >,
-) {
-    const MOVE_RIGHT_INDICES: [usize; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
-    const MOVE_LEFT_INDICES: [usize; 8] = [8, 9, 10, 11, 12, 13, 14, 15];
-    const CLIMB_INDICES: [usize; 8] = [24, 25, 26, 27, 28, 29, 30, 31];
-    const FIXED_INDICE: usize = 16;
-
-    if let Ok((velocity, climber, &jumping, timer, mut sprite)) = players.single_mut() {
-        if !*jumping && timer.just_finished() {
-            let atlas = sprite
-                .texture_atlas
-                .as_mut()
-                .expect("A player should have a TextureAtlas");
-            if velocity.is_moving_right() {
-                atlas.index = next_sprite_index_repeat(&MOVE_RIGHT_INDICES, atlas.index)
-            } else if velocity.is_moving_left() {
-                atlas.index = next_sprite_index_repeat(&MOVE_LEFT_INDICES, atlas.index);
-            } else if climber.climbing {
-                // Climbing
-                let idx = if velocity.is_moving_vertical() {
-                    // Moving
-                    next_sprite_index_repeat(&CLIMB_INDICES, atlas.index)
-                } else {
-                    // Doesn't move during climbing
-                    CLIMB_INDICES[0]
-                };
-                atlas.index = idx;
-            } else {
-                // Doesn't move
-                atlas.index = FIXED_INDICE;
-            }
-        }
-    }
-}
-
-fn animate_jump(
-    mut players: Query<
-        (&Velocity, &Jumping, &AnimationTimer, &mut Sprite),
-        (With<Player>, Without<Dying>),
+        (
+            &Velocity,
+            &Climber,
+            &Jumping,
+            &Facing,
+            Has<Dying>,
+            Option<&Dashing>,
+            Option<&HitStun>,
+            Option<&Attacking>,
+            &mut SpriteAnimation,
+        ),
+        With<Player>,
     >,
 ) {
-    const JUMP_RIGHT_INDICES: [usize; 6] = [0, 1, 2, 3, 4, 5];
-    const JUMP_LEFT_INDICES: [usize; 6] = [6, 7, 8, 9, 10, 11];
-    const JUMP_FRONT_INDICES: [usize; 6] = [12, 13, 14, 15, 16, 17];
-
-    if let Ok((velocity, &jumping, timer, mut sprite)) = players.single_mut() {
-        if *jumping && timer.just_finished() {
-            let atlas = sprite
-                .texture_atlas
-                .as_mut()
-                .expect("A player should have a TextureAtlas");
-            if velocity.is_moving_right() {
-                atlas.index = next_sprite_index_once(&JUMP_RIGHT_INDICES, atlas.index)
-            } else if velocity.is_moving_left() {
-                atlas.index = next_sprite_index_once(&JUMP_LEFT_INDICES, atlas.index);
-            } else {
-                // Doesn't move
-                atlas.index = next_sprite_index_once(&JUMP_FRONT_INDICES, atlas.index);
-            }
+    let Ok((
+        velocity,
+        climber,
+        jumping,
+        facing,
+        dying,
+        dashing,
+        hit_stun,
+        attacking,
+        mut animation,
+    )) = players.single_mut()
+    else {
+        return;
+    };
+    let side = |right, left| match facing {
+        Facing::Right => right,
+        Facing::Left => left,
+    };
+    // Timed actions always play their animation once entirely
+    if dying {
+        animation.play(PLAYER_DEATH_CLIP);
+    } else if let Some(dashing) = dashing {
+        animation.play_at(side("dash_right", "dash_left"), dashing.fraction());
+    } else if let Some(hit_stun) = hit_stun {
+        animation.play_at(side("hurt_right", "hurt_left"), hit_stun.fraction());
+    } else if let Some(attacking) = attacking {
+        animation.play_at(side("attack_right", "attack_left"), attacking.fraction());
+    } else if **jumping {
+        if velocity.is_moving_right() {
+            animation.play("jump_right");
+        } else if velocity.is_moving_left() {
+            animation.play("jump_left");
+        } else {
+            animation.play("jump_front");
         }
+    } else if velocity.is_moving_right() {
+        animation.play("walk_right");
+    } else if velocity.is_moving_left() {
+        animation.play("walk_left");
+    } else if climber.climbing && velocity.is_moving_vertical() {
+        animation.play("climb");
+    } else if climber.climbing {
+        animation.play("climb_idle");
+    } else {
+        animation.play("idle");
     }
 }
 
-fn player_dying(
-    trigger: Trigger<OnAdd, Dying>,
-    mut players: Query<&mut Sprite, With<Player>>,
-    assets: Res<PlayerAssets>,
-) {
-    if let Ok(mut sprite) = players.get_mut(trigger.target()) {
-        sprite.image = assets.death_sprites.clone();
-        sprite.texture_atlas = Some(TextureAtlas {
-            layout: assets.death_atlas_layout.clone(),
-            index: 0,
-        });
-    }
-}
-
-fn animate_death(
+fn tick_hit_stun(
     mut commands: Commands,
+    mut stunned: Query<(Entity, &mut HitStun)>,
     time: Res<Time>,
-    mut players: Query<(Entity, &mut AnimationTimer, &mut Sprite), (With<Player>, With<Dying>)>,
 ) {
-    const DEATH_INDICES: [usize; 6] = [0, 1, 2, 3, 4, 5];
-
-    if let Ok((player_entity, mut timer, mut sprite)) = players.single_mut() {
-        timer.tick(time.delta());
-        if timer.just_finished() {
-            let atlas = sprite
-                .texture_atlas
-                .as_mut()
-                .expect("A player should have a TextureAtlas");
-            match DEATH_INDICES.iter().position(|&v| v == atlas.index) {
-                Some(idx) if idx >= DEATH_INDICES.len() - 1 => {
-                    commands
-                        .entity(player_entity)
-                        .remove::<(Dying, AnimationTimer)>();
-                    commands.trigger(PlayerDeathEvent);
-                }
-                Some(idx) => atlas.index = DEATH_INDICES[idx + 1],
-                None => unreachable!(),
-            }
+    for (entity, mut hit_stun) in &mut stunned {
+        if hit_stun.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<HitStun>();
         }
     }
 }
 
 fn movement(
-    input: Res<ButtonInput<KeyCode>>,
+    input: Res<GameInput>,
+    time: Res<Time>,
     mut query: Query<
         (
+            Entity,
             &mut Velocity,
             &mut Climber,
             &mut Jumping,
             &GroundDetection,
             &Speed,
             &JumpSpeed,
+            &JumpControl,
+            &mut JumpTimings,
+            &mut AirJump,
+            &WallDetection,
             &Items,
             &InWater,
+            &mut Facing,
+            Has<WallJumping>,
         ),
-        With<Player>,
+        (With<Player>, Without<HitStun>, Without<Dashing>),
     >,
+    mut commands: Commands,
 ) {
-    const BOOTS_JUMP_BONUS: f32 = 1.55;
+    const HIGH_JUMP_BONUS: f32 = 1.55;
     const WATER_PENALTY: f32 = 0.4;
-    // const SMALL_JUMP_SPEED: f32 = 160.;
-    // const BIG_JUMP_SPEED: f32 = 280.;
+    const WALL_JUMP_SPEED: f32 = 120.;
 
     for (
+        entity,
         mut velocity,
         mut climber,
         mut jumping,
         ground_detection,
         &speed,
         &jump_speed,
+        jump_control,
+        mut timings,
+        mut air_jump,
+        wall_detection,
         items,
         &in_water,
+        mut facing,
+        wall_jumping,
     ) in &mut query
     {
-        let right = if input.pressed(KeyCode::KeyD) { 1. } else { 0. };
-        let left = if input.pressed(KeyCode::KeyA) { 1. } else { 0. };
-        let up = if input.pressed(KeyCode::KeyW) { 1. } else { 0. };
-        let down = if input.pressed(KeyCode::KeyS) { 1. } else { 0. };
+        let right = if input.pressed(GameKey::Right) {
+            1.
+        } else {
+            0.
+        };
+        let left = if input.pressed(GameKey::Left) { 1. } else { 0. };
+        let up = if input.pressed(GameKey::Up) { 1. } else { 0. };
+        let down = if input.pressed(GameKey::Down) { 1. } else { 0. };
 
-        velocity.linvel.x = (right - left) * *speed;
-        if *in_water {
-            velocity.linvel.x *= WATER_PENALTY;
+        if right > left {
+            facing.set_if_neq(Facing::Right);
+        } else if left > right {
+            facing.set_if_neq(Facing::Left);
+        }
+
+        // Right after a wall jump, the player can't go back to the wall
+        if !wall_jumping {
+            velocity.linvel.x = (right - left) * *speed;
+            if *in_water {
+                velocity.linvel.x *= WATER_PENALTY;
+            }
         }
 
         if climber.intersecting_climbables.is_empty() {
             climber.climbing = false;
-        } else if input.pressed(KeyCode::KeyW) || input.pressed(KeyCode::KeyS) {
+        } else if input.pressed(GameKey::Up) || input.pressed(GameKey::Down) {
             climber.climbing = true;
         }
 
@@ -268,34 +245,74 @@ fn movement(
             velocity.linvel.y = (up - down) * *speed;
         }
 
-        // Jump
-        if input.just_pressed(KeyCode::Space)
-            && !jumping.0
-            && (ground_detection.on_ground || climber.climbing || *in_water)
-        {
+        // Jump: it stays possible a little after leaving the ground (coyote time),
+        // and a jump pressed a little before landing is kept (jump buffer)
+        if ground_detection.on_ground || climber.climbing || *in_water {
+            timings.since_grounded = 0.;
+            air_jump.0 = items.has_ability(Ability::DoubleJump);
+        } else {
+            timings.since_grounded += time.delta_secs();
+        }
+        if input.just_pressed(GameKey::Jump) {
+            timings.since_jump_requested = 0.;
+        } else {
+            timings.since_jump_requested += time.delta_secs();
+        }
+
+        let jump_requested = timings.since_jump_requested <= jump_control.jump_buffer;
+        let wall_side = [Facing::Left, Facing::Right]
+            .into_iter()
+            .find(|&side| wall_detection.on_wall(side));
+        if jump_requested && !jumping.0 && timings.since_grounded <= jump_control.coyote_time {
             jumping.0 = true;
             velocity.linvel.y = *jump_speed;
-            if items.contains(Item::Boots) {
-                velocity.linvel.y *= BOOTS_JUMP_BONUS;
+            if items.has_ability(Ability::HighJump) {
+                velocity.linvel.y *= HIGH_JUMP_BONUS;
             }
             climber.climbing = false;
+            *timings = JumpTimings::default();
+        } else if let Some(wall_side) = wall_side.filter(|_| {
+            jump_requested && !ground_detection.on_ground && items.has_ability(Ability::WallJump)
+        }) {
+            // Jump away from the wall
+            jumping.0 = true;
+            velocity.linvel = Vec2::new(-wall_side.sign() * WALL_JUMP_SPEED, *jump_speed);
+            facing.set_if_neq(if wall_side == Facing::Left {
+                Facing::Right
+            } else {
+                Facing::Left
+            });
+            *timings = JumpTimings::default();
+            commands.entity(entity).insert(WallJumping::default());
+        } else if input.just_pressed(GameKey::Jump)
+            && timings.since_grounded > jump_control.coyote_time
+            && air_jump.0
+        {
+            jumping.0 = true;
+            air_jump.0 = false;
+            velocity.linvel.y = *jump_speed;
+            *timings = JumpTimings::default();
+        }
+
+        // Releasing the jump button early makes a smaller jump
+        if jumping.0 && input.just_released(GameKey::Jump) && velocity.linvel.y > 0. {
+            velocity.linvel.y *= jump_control.jump_cut;
         }
     }
 }
 
 fn dig_hole(
     mut commands: Commands,
-    input: Res<ButtonInput<KeyCode>>,
+    input: Res<GameInput>,
     players: Query<&Transform, With<Player>>,
     ldtk_projects: Query<&LdtkProjectHandle>,
-    levels: Query<(&Transform, &LevelIid), Without<Player>>,
-    diggable_cells: Query<(Entity, &GridCoords, &ChildOf), With<Destructible>>,
-    layers: Query<(Entity, &LayerMetadata)>,
+    levels: Query<(Entity, &Transform, &LevelIid), Without<Player>>,
     ldtk_project_assets: Res<Assets<LdtkProject>>,
     level_selection: Res<LevelSelection>,
+    level_grid: Res<LevelGrid>,
 ) -> Result {
-    let dig_left = input.just_pressed(KeyCode::KeyQ);
-    let dig_right = input.just_pressed(KeyCode::KeyE);
+    let dig_left = input.just_pressed(GameKey::DigLeft);
+    let dig_right = input.just_pressed(GameKey::DigRight);
 
     if !dig_left && !dig_right {
         // Player don't dig
@@ -307,25 +324,17 @@ fn dig_hole(
         .get(ldtk_projects.single()?)
         .ok_or("Project should exist")?;
 
-    let (level_transform, layer_info) = levels
+    let (level_entity, level_transform, layer_info) = levels
         .iter()
-        .filter_map(|(transform, iid)| {
+        .filter_map(|(entity, transform, iid)| {
             let level = ldtk_project.get_raw_level_by_iid(&iid.to_string())?;
             let layer_info = level.layer_instances.as_ref()?.get(COLLISIONS_LAYER)?;
             level_selection
                 .is_match(&LevelIndices::default(), level)
-                .then_some((transform, layer_info))
+                .then_some((entity, transform, layer_info))
         })
         .single()?;
 
-    // get 'Collisions' layer entities where tiles are on
-    let layers_entity = layers
-        .iter()
-        .filter_map(|(layer_entity, metadata)| {
-            (metadata.iid == layer_info.iid).then_some(layer_entity)
-        })
-        .collect::<Vec<_>>();
-
     // get player coords
     let translation = player_transform.translation.xy() - level_transform.translation.xy();
     let player_coord = translation_to_grid_coords(translation, IVec2::splat(layer_info.grid_size));
@@ -342,40 +351,48 @@ fn dig_hole(
     };
 
     // get the digged cell
-    if let Ok(cell_entity) = diggable_cells
-        .iter()
-        .filter(|(_, &coord, ChildOf(le))| layers_entity.contains(le) && coord == cell_coord)
-        .map(|(e, _c, _)| e)
-        .single()
+    if let Some(cell) = level_grid
+        .cell_at(level_entity, cell_coord)
+        .filter(|cell| cell.kind.is_destructible() && !cell.destroyed)
     {
-        commands.trigger_targets(DigEvent, cell_entity);
+        commands.trigger_targets(DigEvent, cell.entity);
     }
     Ok(())
 }
 
 fn enemy_hit_player(
     mut commands: Commands,
-    mut collisions: EventReader<CollisionEvent>,
-    mut players: Query<(Entity, &mut Life), With<Player>>,
-    enemies: Query<&Damage, With<Enemy>>,
+    mut collisions: Collisions<With<Player>, With<Enemy>>,
+    mut players: Query<(Entity, &mut Life, &mut Velocity, &GlobalTransform), With<Player>>,
+    enemies: Query<(&Damage, &GlobalTransform), With<Enemy>>,
 ) -> Result {
-    let (player_entity, mut life) = players.single_mut()?;
-    if let Ok(damage) = collisions
-        .read()
-        .filter_map(start_event_filter)
-        .filter_map(|(&e1, &e2)| enemies.get_either(e1, e2))
-        .filter(|(_damage, _enemy_entity, other_entity)| *other_entity == player_entity)
-        .map(|(damage, _enemy_entity, _player_entity)| damage)
+    const KNOCKBACK_SPEED: Vec2 = Vec2::new(160., 140.);
+    const HIT_STUN_SECS: f32 = 0.4;
+
+    let (player_entity, mut life, mut velocity, player_transform) = players.single_mut()?;
+    if let Ok((damage, enemy_transform)) = collisions
+        .started()
+        .filter_map(|(_player_entity, enemy_entity)| enemies.get(enemy_entity).ok())
         .single()
     {
         life.hit(damage.0);
+        commands.trigger(ImpactEvent::PlayerHurt);
         if life.is_dead() {
             commands.entity(player_entity).insert(Dying);
         } else {
-            // Make player invulnerable
-            commands
-                .entity(player_entity)
-                .insert(Invulnerable::new(Duration::from_secs_f32(2.0), GROUP_ENEMY));
+            // Push the player away from the enemy
+            let direction = if player_transform.translation().x < enemy_transform.translation().x {
+                -1.
+            } else {
+                1.
+            };
+            velocity.linvel = Vec2::new(direction * KNOCKBACK_SPEED.x, KNOCKBACK_SPEED.y);
+
+            // Make player invulnerable, and ignore its inputs for a while
+            commands.entity(player_entity).insert((
+                Invulnerable::new(Duration::from_secs_f32(2.0), GROUP_ENEMY),
+                HitStun::new(Duration::from_secs_f32(HIT_STUN_SECS)),
+            ));
         }
     }
     Ok(())
@@ -391,10 +408,11 @@ fn player_hits_enemy(
         if ground_detectors.get(sensor.ground_detection_entity).is_ok() {
             sensor.intersecting_ground_entities.iter().for_each(|e| {
                 if let Ok((entity, mut life)) = enemies.get_mut(*e) {
-                    life.hit(1);
-                    if life.is_dead() {
-                        // TODO: do not kill it like this
-                        commands.entity(entity).despawn();
+                    if !life.is_dead() {
+                        life.hit(1);
+                        if life.is_dead() {
+                            commands.trigger_targets(EnemyDeathEvent, entity);
+                        }
                     }
                 }
             });
@@ -0,0 +1,82 @@
+use crate::{
+    components::{
+        progress::{CurrentRun, Progress},
+        score::{LevelScoring, Score},
+    },
+    schedule::{InGameSet, InGameState},
+};
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{ldtk::Level, prelude::*};
+
+pub fn progress_plugin(app: &mut App) {
+    app.register_type::<Progress>()
+        .register_type::<CurrentRun>()
+        .insert_resource(Progress::load())
+        .init_resource::<CurrentRun>()
+        .add_systems(OnEnter(InGameState::LevelLoaded), start_run)
+        .add_systems(FixedUpdate, reach_level.in_set(InGameSet::EntityUpdate))
+        .add_systems(OnEnter(InGameState::PlayerEndedLevel), complete_level);
+}
+
+/// The selected level, once the LDtk project is loaded
+fn selected_level<'a>(
+    ldtk_projects: &Query<&LdtkProjectHandle>,
+    ldtk_project_assets: &'a Assets<LdtkProject>,
+    level_selection: &LevelSelection,
+) -> Option<&'a Level> {
+    ldtk_project_assets
+        .get(ldtk_projects.single().ok()?)?
+        .iter_raw_levels()
+        .find(|level| level_selection.is_match(&LevelIndices::default(), level))
+}
+
+fn start_run(
+    mut current_run: ResMut<CurrentRun>,
+    ldtk_projects: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    level_selection: Res<LevelSelection>,
+) {
+    current_run.start_level =
+        selected_level(&ldtk_projects, &ldtk_project_assets, &level_selection)
+            .map(|level| level.identifier.clone());
+}
+
+/// Unlock the levels the player enters
+fn reach_level(
+    mut progress: ResMut<Progress>,
+    ldtk_projects: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    level_selection: Res<LevelSelection>,
+) -> Result {
+    if !level_selection.is_changed() {
+        return Ok(());
+    }
+    let Some(level) = selected_level(&ldtk_projects, &ldtk_project_assets, &level_selection) else {
+        return Ok(());
+    };
+    if progress.reach(&level.identifier) {
+        progress.save()?;
+    }
+    Ok(())
+}
+
+/// Unlock the next level, and keep the best results of the level where the run started
+fn complete_level(
+    mut progress: ResMut<Progress>,
+    score: Res<Score>,
+    current_run: Res<CurrentRun>,
+    ldtk_projects: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+) -> Result {
+    let level = ldtk_project_assets
+        .get(ldtk_projects.single()?)
+        .and_then(|ldtk_project| {
+            ldtk_project
+                .iter_raw_levels()
+                .find(|level| current_run.start_level.as_ref() == Some(&level.identifier))
+        })
+        .ok_or("Run should have started in a level of the project")?;
+    let total = score.total(&LevelScoring::from(level));
+    progress.complete(&level.identifier, score.elapsed_secs, total);
+    progress.save()
+}
@@ -0,0 +1,233 @@
+use crate::{
+    asset_tracking::ResourceHandles,
+    components::{
+        character::Life,
+        input::{GameInput, KeyBindings},
+        item::Items,
+        player::Player,
+        replay::{
+            Recording, Replay, ReplayEnd, ReplayOutcome, ReplayPlayer, StartLevel, LAST_REPLAY_FILE,
+        },
+        score::Score,
+    },
+    in_game::popup_with_images::PopupWithImages,
+    schedule::{game_is_running, GameState, InGameSet, InGameState},
+    storage,
+    utils::args::arg_value,
+};
+use bevy::{input::InputSystem, prelude::*};
+use bevy_ecs_ldtk::prelude::*;
+
+pub fn replay_plugin(app: &mut App) {
+    if let Some(path) = arg_value("--replay") {
+        match Replay::load(&path) {
+            Ok(replay) => {
+                info!("Replay {path}");
+                app.insert_resource(ReplayPlayer::new(replay));
+            }
+            Err(e) => error!("Can't load replay {path}, start normally: {e}"),
+        }
+    }
+
+    app.register_type::<GameInput>()
+        .init_resource::<GameInput>()
+        .register_type::<KeyBindings>()
+        .init_resource::<KeyBindings>()
+        .init_resource::<Recording>()
+        .add_systems(
+            PreUpdate,
+            read_keyboard.after(InputSystem).run_if(not(replaying)),
+        )
+        .add_systems(FixedPostUpdate, consume_input)
+        // Recording
+        .add_systems(
+            OnEnter(InGameState::LevelLoading),
+            start_recording.run_if(not(replaying)),
+        )
+        .add_systems(
+            FixedUpdate,
+            record_input
+                .before(InGameSet::DespawnEntities)
+                .run_if(game_is_running.and(not(replaying))),
+        )
+        .add_systems(
+            OnEnter(InGameState::PlayerEndedLevel),
+            save_recording.run_if(not(replaying)),
+        )
+        .add_systems(
+            OnEnter(InGameState::PlayerDied),
+            save_recording.run_if(not(replaying)),
+        )
+        .add_systems(
+            OnExit(GameState::InGame),
+            save_recording.run_if(not(replaying)),
+        )
+        // Replay
+        .add_systems(
+            OnEnter(InGameState::LevelLoading),
+            select_start_level.run_if(replaying),
+        )
+        .add_systems(
+            FixedUpdate,
+            play_input
+                .before(InGameSet::DespawnEntities)
+                .run_if(game_is_running.and(replaying)),
+        )
+        .add_systems(
+            Update,
+            (
+                skip_menus,
+                close_popups.run_if(in_state(InGameState::ShowPopup)),
+                end_replay,
+            )
+                .run_if(replaying),
+        );
+}
+
+fn replaying(player: Option<Res<ReplayPlayer>>) -> bool {
+    player.is_some()
+}
+
+fn read_keyboard(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut input: ResMut<GameInput>,
+) {
+    input.update(&keyboard, &key_bindings);
+}
+
+fn consume_input(mut input: ResMut<GameInput>) {
+    input.consume();
+}
+
+fn start_recording(mut recording: ResMut<Recording>, level_selection: Res<LevelSelection>) {
+    recording.0 = Some(Replay::new(StartLevel::from(&*level_selection)));
+}
+
+/// Record the input of each game step before it runs, where [play_input] replays it:
+/// a step that sets the next state is recorded, the following ones don't run
+fn record_input(mut recording: ResMut<Recording>, input: Res<GameInput>) {
+    if let Some(replay) = &mut recording.0 {
+        replay.push(*input);
+    }
+}
+
+/// State of the game at the end of a replay
+fn outcome(
+    in_game_state: &InGameState,
+    steps: u32,
+    players: &Query<(&Transform, &Life, &Items), With<Player>>,
+    score: &Score,
+) -> ReplayOutcome {
+    let player = players.single().ok();
+    ReplayOutcome {
+        end: match in_game_state {
+            InGameState::PlayerEndedLevel => ReplayEnd::LevelEnded,
+            InGameState::PlayerDied => ReplayEnd::PlayerDied,
+            _ => ReplayEnd::Interrupted,
+        },
+        steps,
+        player_translation: player.map(|(transform, _, _)| transform.translation.xy().into()),
+        player_life: player.map(|(_, life, _)| life.get()),
+        player_items: player
+            .map(|(_, _, items)| items.iter().map(|item| format!("{item:?}")).collect())
+            .unwrap_or_default(),
+        points: score.points(),
+    }
+}
+
+fn save_recording(
+    mut recording: ResMut<Recording>,
+    in_game_state: Res<State<InGameState>>,
+    players: Query<(&Transform, &Life, &Items), With<Player>>,
+    score: Res<Score>,
+) -> Result {
+    // A game is saved only once
+    let Some(mut replay) = recording.0.take() else {
+        return Ok(());
+    };
+    replay.outcome = Some(outcome(
+        in_game_state.get(),
+        replay.step_count(),
+        &players,
+        &score,
+    ));
+    storage::save(LAST_REPLAY_FILE, &replay)
+}
+
+fn select_start_level(
+    mut level_selection: ResMut<LevelSelection>,
+    replay_player: Res<ReplayPlayer>,
+) {
+    *level_selection = LevelSelection::from(&replay_player.replay.start_level);
+}
+
+fn play_input(
+    mut replay_player: ResMut<ReplayPlayer>,
+    mut input: ResMut<GameInput>,
+    mut next_in_game_state: ResMut<NextState<InGameState>>,
+) {
+    match replay_player.next_input() {
+        Some(next_input) => *input = next_input,
+        None => {
+            // Stop the game before it runs a step that was not recorded
+            replay_player.finished = true;
+            next_in_game_state.set(InGameState::Pause);
+        }
+    }
+}
+
+/// Start the game as soon as the assets are loaded
+fn skip_menus(
+    game_state: Res<State<GameState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    resources: Res<ResourceHandles>,
+) {
+    if *game_state != GameState::InGame && resources.is_all_done() {
+        next_game_state.set(GameState::InGame);
+    }
+}
+
+/// Popups are closed by the player, outside of the recorded game steps, once the game
+/// is paused to show them
+fn close_popups(mut commands: Commands, popups: Query<Entity, With<PopupWithImages>>) {
+    for entity in &popups {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Report the state of the game at the end of the replay, and quit
+fn end_replay(
+    mut replay_player: ResMut<ReplayPlayer>,
+    in_game_state: Res<State<InGameState>>,
+    players: Query<(&Transform, &Life, &Items), With<Player>>,
+    score: Res<Score>,
+    mut app_exit_events: EventWriter<AppExit>,
+    mut reported: Local<bool>,
+) {
+    let ended = matches!(
+        in_game_state.get(),
+        InGameState::PlayerEndedLevel | InGameState::PlayerDied
+    );
+    if *reported || !(ended || replay_player.finished) {
+        return;
+    }
+    *reported = true;
+    replay_player.finished = true;
+
+    let outcome = outcome(in_game_state.get(), replay_player.steps, &players, &score);
+    info!(
+        "Replay finished after {}/{} steps: {outcome:?}",
+        replay_player.steps,
+        replay_player.replay.step_count()
+    );
+    match &replay_player.replay.outcome {
+        Some(expected) if *expected != outcome => {
+            error!("Replay differs from the recorded game: {expected:?}");
+            app_exit_events.write(AppExit::error());
+        }
+        _ => {
+            app_exit_events.write(AppExit::Success);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{
+    components::{
+        enemy::EnemyDeathEvent,
+        high_score::HighScores,
+        item::{ChestOpenedEvent, Item},
+        score::Score,
+    },
+    schedule::{InGameSet, InGameState},
+};
+use bevy::prelude::*;
+
+pub fn score_plugin(app: &mut App) {
+    app.register_type::<Score>()
+        .register_type::<HighScores>()
+        .init_resource::<Score>()
+        .insert_resource(HighScores::load())
+        .add_systems(OnEnter(InGameState::LevelLoading), reset_score)
+        .add_systems(FixedUpdate, tick_level_time.in_set(InGameSet::EntityUpdate))
+        .add_observer(count_enemy)
+        .add_observer(count_chest);
+}
+
+fn reset_score(mut score: ResMut<Score>) {
+    *score = Score::default();
+}
+
+fn tick_level_time(mut score: ResMut<Score>, time: Res<Time>) {
+    score.elapsed_secs += time.delta_secs();
+}
+
+fn count_enemy(_trigger: Trigger<EnemyDeathEvent>, mut score: ResMut<Score>) {
+    score.enemies += 1;
+}
+
+fn count_chest(trigger: Trigger<ChestOpenedEvent>, mut score: ResMut<Score>) {
+    score.chests += 1;
+    score.gems += trigger.0.iter().filter(|&&item| item == Item::Gem).count() as u32;
+}
@@ -0,0 +1,81 @@
+use crate::{
+    components::{
+        progress::CurrentRun,
+        speedrun::{PersonalBests, ShowSplits, Speedrun, PERSONAL_BESTS_FILE},
+    },
+    schedule::{InGameSet, InGameState},
+    storage,
+};
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+pub fn speedrun_plugin(app: &mut App) {
+    app.register_type::<Speedrun>()
+        .register_type::<PersonalBests>()
+        .register_type::<ShowSplits>()
+        .init_resource::<Speedrun>()
+        .init_resource::<ShowSplits>()
+        .insert_resource(storage::load::<PersonalBests>(PERSONAL_BESTS_FILE))
+        .add_systems(OnEnter(InGameState::LevelLoading), reset_speedrun)
+        .add_systems(OnEnter(InGameState::PlayerEndedLevel), end_speedrun)
+        .add_systems(Update, toggle_splits.run_if(in_state(InGameState::Running)))
+        .add_systems(
+            FixedUpdate,
+            (tick_speedrun, split_on_level_change)
+                .chain()
+                .in_set(InGameSet::EntityUpdate),
+        );
+}
+
+fn reset_speedrun(mut speedrun: ResMut<Speedrun>) {
+    *speedrun = Speedrun::default();
+}
+
+fn tick_speedrun(mut speedrun: ResMut<Speedrun>, time: Res<Time>) {
+    speedrun.run.tick(time.delta());
+    speedrun.segment.tick(time.delta());
+}
+
+fn split_on_level_change(
+    mut speedrun: ResMut<Speedrun>,
+    ldtk_projects: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    level_selection: Res<LevelSelection>,
+) {
+    let Some(level) = ldtk_projects
+        .single()
+        .ok()
+        .and_then(|handle| ldtk_project_assets.get(handle))
+        .and_then(|ldtk_project| {
+            ldtk_project
+                .iter_raw_levels()
+                .find(|level| level_selection.is_match(&LevelIndices::default(), level))
+        })
+    else {
+        return;
+    };
+    if speedrun.level.as_ref() != Some(&level.identifier) {
+        speedrun.split(Some(level.identifier.clone()));
+    }
+}
+
+/// Only a new game is a complete run, a run from the level select menu only keeps its segments
+fn end_speedrun(
+    mut speedrun: ResMut<Speedrun>,
+    mut personal_bests: ResMut<PersonalBests>,
+    current_run: Res<CurrentRun>,
+) -> Result {
+    speedrun.split(None);
+    if current_run.from_level_select {
+        personal_bests.record_segments(&speedrun.splits);
+    } else if personal_bests.record(&speedrun.splits) {
+        info!("New personal best: {:.2}s", speedrun.run.elapsed_secs());
+    }
+    storage::save(PERSONAL_BESTS_FILE, &*personal_bests)
+}
+
+fn toggle_splits(mut show_splits: ResMut<ShowSplits>, input: Res<ButtonInput<KeyCode>>) {
+    if input.just_pressed(KeyCode::KeyT) {
+        show_splits.0 = !show_splits.0;
+    }
+}
@@ -0,0 +1,92 @@
+//! Texts of the menus in the [Language] chosen in the [Settings].
+
+use crate::settings::Settings;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub fn localization_plugin(app: &mut App) {
+    app.add_systems(Update, localize_texts);
+}
+
+/// Language of the texts of the game, they are written in English
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, Reflect)]
+pub enum Language {
+    #[default]
+    English,
+    Italian,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Italian];
+
+    /// Name of the language, written in this language
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Italian => "Italiano",
+        }
+    }
+
+    /// Translations of the English texts
+    fn texts(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => &[],
+            Language::Italian => ITALIAN,
+        }
+    }
+
+    /// Translation of an English text, or the text itself if it isn't translated
+    pub fn translate(self, text: &str) -> &str {
+        self.texts()
+            .iter()
+            .find(|(english, _)| *english == text)
+            .map_or(text, |(_, translation)| *translation)
+    }
+}
+
+// The default font only has ASCII characters
+const ITALIAN: &[(&str, &str)] = &[
+    ("Back", "Indietro"),
+    ("Back to game", "Torna al gioco"),
+    ("Back to menu", "Torna al menu"),
+    ("Change", "Cambia"),
+    ("Chest opened", "Forziere aperto"),
+    ("Display", "Schermo"),
+    ("Exit", "Esci"),
+    ("Ghost", "Fantasma"),
+    ("High scores", "Migliori punteggi"),
+    ("Language", "Lingua"),
+    ("Level completed", "Livello completato"),
+    ("Level transition", "Transizione di livello"),
+    ("Loading...", "Caricamento..."),
+    ("Map", "Mappa"),
+    ("New game", "Nuova partita"),
+    ("Pause", "Pausa"),
+    ("Press a key...", "Premi un tasto..."),
+    ("Quit game", "Esci dalla partita"),
+    ("Resolution", "Risoluzione"),
+    ("Screen shake", "Tremolio dello schermo"),
+    ("Select level", "Scegli il livello"),
+    ("Settings", "Impostazioni"),
+    ("Vsync", "Sincronia verticale"),
+    ("You died !", "Sei morto !"),
+];
+
+/// A [Text] written in English, shown in the [Language] of the [Settings]
+#[derive(Component, Clone, Debug)]
+pub struct Localized(pub String);
+
+/// Translate the new texts, and all of them when the language changes
+fn localize_texts(
+    settings: Res<Settings>,
+    mut shown_language: Local<Language>,
+    mut texts: Query<(Ref<Localized>, &mut Text)>,
+) {
+    let language_changed = *shown_language != settings.language;
+    *shown_language = settings.language;
+    for (localized, mut text) in &mut texts {
+        if localized.is_added() || language_changed {
+            text.0 = settings.language.translate(&localized.0).to_string();
+        }
+    }
+}
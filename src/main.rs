@@ -3,9 +3,13 @@ mod camera;
 mod components;
 mod cursor;
 mod in_game;
+mod localization;
 mod main_menu;
 mod schedule;
+mod settings;
+mod settings_menu;
 mod splash;
+mod storage;
 mod theme;
 mod ui;
 mod utils;
@@ -13,25 +17,34 @@ mod utils;
 #[cfg(feature = "dev")]
 mod debug;
 
+#[cfg(test)]
+mod tests;
+
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 const PIXELS_PER_METER: f32 = 100.0;
 
 fn main() {
+    // The window is shown with the saved settings from the start
+    let mut window = Window {
+        title: "Load-Runner".into(),
+        position: WindowPosition::At(IVec2::ZERO),
+        ..Default::default()
+    };
+    let settings = settings::Settings::load();
+    settings.apply_to_window(&mut window);
+
     let mut app = App::new();
+    app.insert_resource(settings);
     app.add_plugins((
         DefaultPlugins
             .set(WindowPlugin {
-                primary_window: Some(Window {
-                    title: "Load-Runner".into(),
-                    position: WindowPosition::At(IVec2::ZERO),
-                    ..Default::default()
-                }),
+                primary_window: Some(window),
                 ..Default::default()
             })
             .set(ImagePlugin::default_nearest()),
-        RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(PIXELS_PER_METER),
+        physics_plugin,
     ))
     .add_plugins((
         ui::progressbar::plugin,
@@ -48,6 +61,10 @@ fn main() {
         splash::splash_plugin,
         main_menu::main_menu_plugin,
         in_game::InGamePlugins,
+        // After the plugins of the resources it sets
+        settings::settings_plugin,
+        settings_menu::settings_menu_plugin,
+        localization::localization_plugin,
     ));
 
     #[cfg(feature = "dev")]
@@ -55,3 +72,14 @@ fn main() {
 
     app.run();
 }
+
+/// Physics steps with the game, so that a game is reproducible from its inputs
+fn physics_plugin(app: &mut App) {
+    app.insert_resource(TimestepMode::Fixed {
+        dt: Time::<Fixed>::default().timestep().as_secs_f32(),
+        substeps: 1,
+    })
+    .add_plugins(
+        RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(PIXELS_PER_METER).in_fixed_schedule(),
+    );
+}
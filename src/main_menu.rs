@@ -1,18 +1,60 @@
 use crate::{
-    components::despawn_all,
+    components::{
+        despawn_all,
+        high_score::{level_version, HighScores},
+        level::{level_start, LdtkProjectPath},
+        map::level_thumbnail,
+        progress::{CurrentRun, LevelLock, LevelRecord, Progress},
+        speedrun::format_time,
+    },
     schedule::GameState,
-    theme::{palette::MAIN_MENU_BACKGROUND, widget},
+    settings::ShowSettingsEvent,
+    theme::{
+        palette::{MAIN_MENU_BACKGROUND, MENU_BUTTON_PALETTE},
+        widget,
+    },
 };
-use bevy::{app::AppExit, color::palettes::css::GRAY, prelude::*};
+use bevy::{
+    app::AppExit,
+    color::palettes::css::GRAY,
+    ecs::spawn::{SpawnIter, SpawnWith},
+    prelude::*,
+};
+use bevy_ecs_ldtk::{ldtk::Level, prelude::*};
 
 pub fn main_menu_plugin(app: &mut App) {
     app.add_systems(OnEnter(GameState::Menu), (set_background, spawn_menu))
-        .add_systems(OnExit(GameState::Menu), despawn_all::<MainMenu>);
+        .add_systems(
+            OnExit(GameState::Menu),
+            (
+                despawn_all::<MainMenu>,
+                despawn_all::<HighScoresMenu>,
+                despawn_all::<LevelSelectMenu>,
+            ),
+        )
+        .add_systems(
+            Update,
+            (fill_level_select_menu, fill_high_scores_menu).run_if(in_state(GameState::Menu)),
+        );
 }
 
 #[derive(Component)]
 struct MainMenu;
 
+/// High scores of the levels of the LDtk project, shown once it is loaded
+#[derive(Component)]
+struct HighScoresMenu {
+    project: Handle<LdtkProject>,
+    filled: bool,
+}
+
+/// Levels of the LDtk project, shown once it is loaded
+#[derive(Component)]
+struct LevelSelectMenu {
+    project: Handle<LdtkProject>,
+    filled: bool,
+}
+
 fn main_menu() -> impl Bundle {
     (
         MainMenu,
@@ -22,11 +64,173 @@ fn main_menu() -> impl Bundle {
         children![
             widget::header("Load-Runner"),
             widget::menu_button("New game", on_new_game),
+            widget::menu_button("Select level", on_select_level),
+            widget::menu_button("High scores", on_high_scores),
+            widget::menu_button("Settings", on_settings),
             widget::menu_button("Exit", on_exit),
         ],
     )
 }
 
+fn high_scores_menu(content: impl Bundle) -> impl Bundle {
+    (
+        widget::ui_root("HighScoresMenu"),
+        BackgroundColor(MAIN_MENU_BACKGROUND),
+        GlobalZIndex(3),
+        children![(
+            widget::popup(),
+            children![
+                widget::popup_title("High scores"),
+                (widget::scroll_list(Val::Vh(60.0)), children![content]),
+                widget::menu_button("Back", on_back),
+            ],
+        )],
+    )
+}
+
+/// The high scores of the current version of each level
+fn high_scores_lines<'a>(
+    high_scores: &HighScores,
+    levels: impl Iterator<Item = &'a Level>,
+) -> String {
+    let mut lines = Vec::new();
+    for level in levels {
+        let entries = high_scores.entries(&level.identifier, level_version(level));
+        if !entries.is_empty() {
+            lines.push(format!("{}:", level.identifier));
+            for (rank, entry) in entries.iter().enumerate() {
+                lines.push(format!(
+                    "{:>2}. {:<12} {:>6}",
+                    rank + 1,
+                    entry.name,
+                    entry.score
+                ));
+            }
+        } else if high_scores
+            .levels
+            .get(&level.identifier)
+            .is_some_and(|level_high_scores| !level_high_scores.entries.is_empty())
+        {
+            lines.push(format!(
+                "{}: only scores of a previous version",
+                level.identifier
+            ));
+        }
+    }
+    if lines.is_empty() {
+        lines.push("No high score yet".to_string());
+    }
+    lines.join("\n")
+}
+
+/// Width of the level thumbnails, in pixels
+const THUMBNAIL_WIDTH: f32 = 96.;
+
+fn level_select_menu(content: impl Bundle) -> impl Bundle {
+    (
+        widget::ui_root("LevelSelectMenu"),
+        BackgroundColor(MAIN_MENU_BACKGROUND),
+        GlobalZIndex(3),
+        children![(
+            widget::popup_sized(Val::Percent(90.0)),
+            children![
+                widget::popup_title("Select level"),
+                content,
+                widget::menu_button("Back", on_back),
+            ],
+        )],
+    )
+}
+
+/// A level of the [LevelSelectMenu], that can be played if it is unlocked
+fn level_entry(
+    level: &Level,
+    thumbnail: Option<Handle<Image>>,
+    lock: LevelLock,
+    record: Option<LevelRecord>,
+) -> impl Bundle {
+    let identifier = level.identifier.clone();
+    let iid = level.iid.clone();
+    let thumbnail_height = THUMBNAIL_WIDTH * level.px_hei as f32 / level.px_wid.max(1) as f32;
+    let status = match (lock, record) {
+        (LevelLock::Locked, _) => "Locked".to_string(),
+        (_, Some(record)) => format!(
+            "{}  {} pts",
+            format_time(record.best_secs),
+            record.best_score
+        ),
+        _ => "Not completed".to_string(),
+    };
+    (
+        Name::new(format!("LevelEntry {identifier}")),
+        Node {
+            margin: UiRect::all(Val::Px(6.0)),
+            row_gap: Val::Px(4.0),
+            ..widget::vsizer()
+        },
+        Children::spawn((
+            Spawn((
+                Node {
+                    width: Val::Px(THUMBNAIL_WIDTH),
+                    height: Val::Px(thumbnail_height),
+                    ..Default::default()
+                },
+                BackgroundColor(Color::BLACK),
+                thumbnail.map(ImageNode::new).unwrap_or_default(),
+            )),
+            Spawn((
+                Text(status),
+                TextFont::from_font_size(14.),
+                TextColor(Color::WHITE),
+            )),
+            SpawnWith(move |parent: &mut ChildSpawner| {
+                if lock == LevelLock::Locked {
+                    parent.spawn(widget::label(identifier));
+                } else {
+                    parent.spawn(widget::button_base(
+                        identifier,
+                        MENU_BUTTON_PALETTE,
+                        move |_trigger: Trigger<Pointer<Click>>,
+                              mut level_selection: ResMut<LevelSelection>,
+                              mut current_run: ResMut<CurrentRun>,
+                              mut next_game_state: ResMut<NextState<GameState>>| {
+                            *level_selection = LevelSelection::iid(iid.clone());
+                            current_run.from_level_select = true;
+                            next_game_state.set(GameState::InGame);
+                        },
+                        (
+                            Node {
+                                padding: UiRect::axes(Val::Px(12.0), Val::Px(4.0)),
+                                ..widget::centered()
+                            },
+                            BorderRadius::MAX,
+                        ),
+                    ));
+                }
+            }),
+        )),
+    )
+}
+
+/// The levels of a world, in their LDtk order
+fn world_entries(name: &str, entries: Vec<impl Bundle>) -> impl Bundle {
+    (
+        Name::new(format!("World {name}")),
+        widget::vsizer(),
+        children![
+            widget::label(name.to_string()),
+            (
+                Node {
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::Center,
+                    ..widget::hsizer()
+                },
+                Children::spawn(SpawnIter(entries.into_iter())),
+            ),
+        ],
+    )
+}
+
 fn set_background(mut commands: Commands) {
     commands.insert_resource(ClearColor(GRAY.into()));
 }
@@ -37,11 +241,149 @@ fn spawn_menu(mut commands: Commands) {
 
 fn on_new_game(
     _trigger: Trigger<Pointer<Click>>,
+    mut level_selection: ResMut<LevelSelection>,
+    mut current_run: ResMut<CurrentRun>,
     mut next_game_state: ResMut<NextState<GameState>>,
 ) {
+    // Start from the first level, even after a level was selected
+    *level_selection = LevelSelection::Uid(0);
+    current_run.from_level_select = false;
     next_game_state.set(GameState::InGame);
 }
 
+fn on_select_level(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    ldtk_project_path: Res<LdtkProjectPath>,
+) {
+    commands.spawn((
+        LevelSelectMenu {
+            project: asset_server.load(&ldtk_project_path.0),
+            filled: false,
+        },
+        level_select_menu(widget::localized_label("Loading...")),
+    ));
+}
+
+/// List the levels grouped by world, once the project is loaded
+fn fill_level_select_menu(
+    mut commands: Commands,
+    mut menus: Query<(Entity, &mut LevelSelectMenu)>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    progress: Res<Progress>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (entity, mut menu) in &mut menus {
+        if menu.filled {
+            continue;
+        }
+        let Some(ldtk_project) = ldtk_project_assets.get(&menu.project) else {
+            continue;
+        };
+        let json = ldtk_project.json_data();
+        let worlds = if json.worlds.is_empty() {
+            vec![("Levels", &json.levels)]
+        } else {
+            json.worlds
+                .iter()
+                .map(|world| (world.identifier.as_str(), &world.levels))
+                .collect()
+        };
+        let world_nodes = worlds
+            .into_iter()
+            .map(|(name, levels)| {
+                // Only the levels where the player can start
+                let levels = levels
+                    .iter()
+                    .filter(|level| level_start(level, json.default_grid_size).is_some())
+                    .collect::<Vec<_>>();
+                let entries = levels
+                    .iter()
+                    .enumerate()
+                    .map(|(index, level)| {
+                        let previous = index
+                            .checked_sub(1)
+                            .map(|previous| levels[previous].identifier.as_str());
+                        let thumbnail = level_thumbnail(level)
+                            .inspect_err(|e| warn!("No thumbnail of {}: {e}", level.identifier))
+                            .ok()
+                            .map(|image| images.add(image));
+                        level_entry(
+                            level,
+                            thumbnail,
+                            LevelLock::of(&progress, &level.identifier, previous),
+                            progress.record(&level.identifier).copied(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                world_entries(name, entries)
+            })
+            .collect::<Vec<_>>();
+
+        menu.filled = true;
+        commands
+            .entity(entity)
+            .despawn_related::<Children>()
+            .insert(level_select_menu((
+                widget::scroll_list(Val::Vh(70.0)),
+                Children::spawn(SpawnIter(world_nodes.into_iter())),
+            )));
+    }
+}
+
+fn on_high_scores(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    ldtk_project_path: Res<LdtkProjectPath>,
+) {
+    commands.spawn((
+        HighScoresMenu {
+            project: asset_server.load(&ldtk_project_path.0),
+            filled: false,
+        },
+        high_scores_menu(widget::localized_label("Loading...")),
+    ));
+}
+
+/// List the high scores, once the project is loaded to know the versions of the levels
+fn fill_high_scores_menu(
+    mut commands: Commands,
+    mut menus: Query<(Entity, &mut HighScoresMenu)>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+    high_scores: Res<HighScores>,
+) {
+    for (entity, mut menu) in &mut menus {
+        if menu.filled {
+            continue;
+        }
+        let Some(ldtk_project) = ldtk_project_assets.get(&menu.project) else {
+            continue;
+        };
+        menu.filled = true;
+        let lines = high_scores_lines(&high_scores, ldtk_project.iter_raw_levels());
+        commands
+            .entity(entity)
+            .despawn_related::<Children>()
+            .insert(high_scores_menu(widget::label(lines)));
+    }
+}
+
+fn on_settings(_trigger: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(ShowSettingsEvent);
+}
+
+fn on_back(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    menus: Query<Entity, Or<(With<HighScoresMenu>, With<LevelSelectMenu>)>>,
+) {
+    for menu in &menus {
+        commands.entity(menu).despawn();
+    }
+}
+
 fn on_exit(_trigger: Trigger<Pointer<Click>>, mut app_exit_events: EventWriter<AppExit>) {
     app_exit_events.write(AppExit::Success);
 }
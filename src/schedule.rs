@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use bevy_rapier2d::prelude::PhysicsSet;
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, SystemSet)]
 pub enum InGameSet {
@@ -25,6 +26,8 @@ pub enum InGameState {
     LevelLoading,
     LevelLoaded,
     Running,
+    /// The player crossed the boundary of a neighbour level, see [LevelTransition](crate::components::level::LevelTransition)
+    LevelTransition,
     Pause,
     PlayerEndedLevel,
     PlayerDied,
@@ -35,20 +38,29 @@ pub fn schedule_plugin(app: &mut App) {
     app.init_state::<GameState>()
         .init_state::<InGameState>()
         .enable_state_scoped_entities::<InGameState>()
+        // The game runs with a fixed timestep, so that a game is reproducible from its inputs
         .configure_sets(
-            Update,
+            FixedUpdate,
             (
                 InGameSet::DespawnEntities,
                 // apply_deffer will be added here
                 InGameSet::UserInput,
                 InGameSet::EntityUpdate,
+                // the physics simulation steps here
                 InGameSet::CollisionDetection,
             )
                 .chain()
                 .run_if(game_is_running),
         )
+        .configure_sets(
+            FixedUpdate,
+            (
+                InGameSet::EntityUpdate.before(PhysicsSet::SyncBackend),
+                InGameSet::CollisionDetection.after(PhysicsSet::Writeback),
+            ),
+        )
         .add_systems(
-            Update,
+            FixedUpdate,
             ApplyDeferred
                 .after(InGameSet::DespawnEntities)
                 .before(InGameSet::UserInput),
@@ -57,11 +69,15 @@ pub fn schedule_plugin(app: &mut App) {
         .add_systems(OnExit(GameState::InGame), end_game);
 }
 
-fn game_is_running(
+pub fn game_is_running(
     game_state: Res<State<GameState>>,
     in_game_state: Res<State<InGameState>>,
+    next_in_game_state: Res<NextState<InGameState>>,
 ) -> bool {
-    *game_state == GameState::InGame && *in_game_state == InGameState::Running
+    *game_state == GameState::InGame
+        && *in_game_state == InGameState::Running
+        // Don't run another game step before a pending state change is applied
+        && matches!(*next_in_game_state, NextState::Unchanged)
 }
 
 fn new_game(
@@ -0,0 +1,190 @@
+//! Player preferences, saved between game sessions and applied as soon as they change.
+
+use crate::{
+    components::{
+        ghost::{Ghost, GhostSettings},
+        input::{key_from_name, key_name, GameKey, KeyBindings},
+        juice::{CameraShake, ShakeSettings},
+        level::{LevelTransitionKind, LevelTransitionSettings},
+    },
+    localization::Language,
+    storage,
+};
+use bevy::{
+    audio::Volume,
+    prelude::*,
+    window::{MonitorSelection, PresentMode, PrimaryWindow, VideoModeSelection, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+/// Window sizes that can be chosen, in logical pixels
+pub const RESOLUTIONS: [(u32, u32); 4] = [(1280, 720), (1600, 900), (1920, 1080), (2560, 1440)];
+
+/// Apply the [Settings], that are loaded once when the game starts to open the window with them
+pub fn settings_plugin(app: &mut App) {
+    app.register_type::<Settings>()
+        .add_systems(Update, apply_settings.run_if(resource_changed::<Settings>));
+}
+
+/// How the game window is shown
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, Reflect)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [
+        DisplayMode::Windowed,
+        DisplayMode::Borderless,
+        DisplayMode::Fullscreen,
+    ];
+
+    fn window_mode(self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+            DisplayMode::Fullscreen => {
+                WindowMode::Fullscreen(MonitorSelection::Current, VideoModeSelection::Current)
+            }
+        }
+    }
+}
+
+/// Preferences of the player
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Settings {
+    pub display_mode: DisplayMode,
+    /// Size of the window, when not in full screen
+    pub resolution: (u32, u32),
+    pub vsync: bool,
+    /// Global volume, from 0 to 1
+    pub volume: f32,
+    /// The [GameKey]s bound to another key than their default one, by [key_name]
+    pub key_bindings: Vec<(GameKey, String)>,
+    pub screen_shake: bool,
+    pub ghost: bool,
+    pub level_transition: LevelTransitionKind,
+    pub language: Language,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            display_mode: DisplayMode::default(),
+            resolution: RESOLUTIONS[0],
+            vsync: true,
+            volume: 1.,
+            key_bindings: Vec::new(),
+            screen_shake: true,
+            ghost: true,
+            level_transition: LevelTransitionKind::default(),
+            language: Language::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        storage::load(SETTINGS_FILE)
+    }
+
+    pub fn save(&self) -> Result {
+        storage::save(SETTINGS_FILE, self)
+    }
+
+    pub fn key_bindings(&self) -> KeyBindings {
+        let mut key_bindings = KeyBindings::default();
+        for (key, name) in &self.key_bindings {
+            match key_from_name(name) {
+                Some(key_code) => key_bindings.bind(*key, key_code),
+                None => warn!("Ignore unknown key {name} bound to {key:?}"),
+            }
+        }
+        key_bindings
+    }
+
+    /// Bind a key to a [GameKey], swapping it with the [GameKey] the key was bound to
+    pub fn bind(&mut self, key: GameKey, key_code: KeyCode) {
+        let key_bindings = self.key_bindings();
+        let previous_key_code = key_bindings.key_code(key);
+        let conflicting_key = GameKey::ALL
+            .into_iter()
+            .find(|&other| other != key && key_bindings.key_code(other) == key_code);
+        self.set_binding(key, key_code);
+        if let Some(other) = conflicting_key {
+            self.set_binding(other, previous_key_code);
+        }
+    }
+
+    fn set_binding(&mut self, key: GameKey, key_code: KeyCode) {
+        self.key_bindings.retain(|(bound_key, _)| *bound_key != key);
+        if key_code != key.key_code() {
+            self.key_bindings.push((key, key_name(key_code)));
+        }
+    }
+
+    /// Show a window with these settings
+    pub fn apply_to_window(&self, window: &mut Window) {
+        window.mode = self.display_mode.window_mode();
+        let (width, height) = self.resolution;
+        window.resolution.set(width as f32, height as f32);
+        window.present_mode = if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+    }
+}
+
+fn apply_settings(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    global_volume: Option<ResMut<GlobalVolume>>,
+    mut key_bindings: ResMut<KeyBindings>,
+    mut shake_settings: ResMut<ShakeSettings>,
+    mut shakes: Query<&mut CameraShake>,
+    mut ghost_settings: ResMut<GhostSettings>,
+    ghosts: Query<Entity, With<Ghost>>,
+    mut transition_settings: ResMut<LevelTransitionSettings>,
+) -> Result {
+    for mut window in &mut windows {
+        settings.apply_to_window(&mut window);
+    }
+    if let Some(mut global_volume) = global_volume {
+        global_volume.volume = Volume::Linear(settings.volume);
+    }
+    *key_bindings = settings.key_bindings();
+
+    shake_settings.enabled = settings.screen_shake;
+    if !settings.screen_shake {
+        for mut shake in &mut shakes {
+            shake.trauma = 0.;
+        }
+    }
+    // The ghost will be spawned back when the game restarts
+    ghost_settings.enabled = settings.ghost;
+    if !settings.ghost {
+        for ghost in &ghosts {
+            commands.entity(ghost).despawn();
+        }
+    }
+    transition_settings.kind = settings.level_transition;
+
+    // Loaded settings don't need to be saved again
+    if !settings.is_added() {
+        settings.save()?;
+    }
+    Ok(())
+}
+
+/// Show the settings screen, over the current menu
+#[derive(Event, Debug)]
+pub struct ShowSettingsEvent;
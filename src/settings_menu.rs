@@ -0,0 +1,275 @@
+use crate::{
+    components::{
+        despawn_all,
+        input::{key_name, GameKey, BINDABLE_KEYS},
+        level::LevelTransitionKind,
+    },
+    localization::Language,
+    schedule::{GameState, InGameState},
+    settings::{DisplayMode, Settings, ShowSettingsEvent, RESOLUTIONS},
+    theme::{
+        controls::{Slider, ValueChangeEvent},
+        focus::FocusSystems,
+        palette::MAIN_MENU_BACKGROUND,
+        widget,
+    },
+};
+use bevy::{ecs::spawn::SpawnIter, prelude::*};
+
+pub fn settings_menu_plugin(app: &mut App) {
+    app.init_resource::<Rebinding>()
+        .add_observer(show_settings_menu)
+        .add_systems(
+            Update,
+            (
+                rebind_key.before(FocusSystems),
+                update_key_binding_texts
+                    .after(rebind_key)
+                    .run_if(resource_changed::<Settings>.or(resource_changed::<Rebinding>)),
+            ),
+        )
+        .add_systems(OnExit(GameState::Menu), despawn_all::<SettingsMenu>)
+        .add_systems(OnExit(InGameState::Pause), despawn_all::<SettingsMenu>);
+}
+
+#[derive(Component)]
+struct SettingsMenu;
+
+/// The [GameKey] waiting for a key press to be bound to it
+#[derive(Resource, Default)]
+struct Rebinding(Option<GameKey>);
+
+/// Shows the key bound to a [GameKey]
+#[derive(Component)]
+struct KeyBindingText(GameKey);
+
+/// Step of the volume slider
+const VOLUME_STEP: f32 = 0.1;
+
+fn settings_menu(settings: &Settings) -> impl Bundle {
+    let resolution_index = RESOLUTIONS
+        .iter()
+        .position(|&resolution| resolution == settings.resolution)
+        .unwrap_or_default();
+    let key_rows = GameKey::ALL
+        .into_iter()
+        .map(|key| {
+            widget::setting_row(
+                format!("{key:?}"),
+                (
+                    Node {
+                        column_gap: Val::Px(12.0),
+                        ..widget::hsizer()
+                    },
+                    children![
+                        (widget::label(""), KeyBindingText(key)),
+                        widget::button_small(
+                            "Change",
+                            move |_trigger: Trigger<Pointer<Click>>,
+                                  mut rebinding: ResMut<Rebinding>| {
+                                rebinding.0 = Some(key);
+                            },
+                        ),
+                    ],
+                ),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    (
+        SettingsMenu,
+        widget::ui_root("SettingsMenu"),
+        BackgroundColor(MAIN_MENU_BACKGROUND),
+        GlobalZIndex(5),
+        children![(
+            widget::popup_sized(Val::Percent(60.0)),
+            Children::spawn((
+                Spawn(widget::popup_title("Settings")),
+                Spawn((
+                    Name::new("SettingsRows"),
+                    Node {
+                        width: Val::Percent(90.0),
+                        padding: UiRect::vertical(Val::Px(10.0)),
+                        ..widget::vsizer()
+                    },
+                    children![(
+                        widget::scroll_list(Val::Vh(60.0)),
+                        Children::spawn((
+                            Spawn(widget::setting_row(
+                                "Display",
+                                widget::dropdown(
+                                    DisplayMode::ALL.map(|mode| format!("{mode:?}")).to_vec(),
+                                    DisplayMode::ALL
+                                        .iter()
+                                        .position(|&mode| mode == settings.display_mode)
+                                        .unwrap_or_default(),
+                                    on_select_display_mode,
+                                ),
+                            )),
+                            Spawn(widget::setting_row(
+                                "Resolution",
+                                widget::dropdown(
+                                    RESOLUTIONS
+                                        .map(|(width, height)| format!("{width}x{height}"))
+                                        .to_vec(),
+                                    resolution_index,
+                                    on_select_resolution,
+                                ),
+                            )),
+                            Spawn(widget::setting_row(
+                                "Vsync",
+                                widget::checkbox(settings.vsync, on_toggle_vsync),
+                            )),
+                            Spawn(widget::setting_row(
+                                "Volume",
+                                widget::slider(
+                                    Slider {
+                                        value: settings.volume,
+                                        min: 0.,
+                                        max: 1.,
+                                        step: VOLUME_STEP,
+                                    },
+                                    on_change_volume,
+                                ),
+                            )),
+                            Spawn(widget::setting_row(
+                                "Screen shake",
+                                widget::checkbox(settings.screen_shake, on_toggle_shake),
+                            )),
+                            Spawn(widget::setting_row(
+                                "Ghost",
+                                widget::checkbox(settings.ghost, on_toggle_ghost),
+                            )),
+                            Spawn(widget::setting_row(
+                                "Level transition",
+                                widget::dropdown(
+                                    LevelTransitionKind::ALL
+                                        .map(|kind| format!("{kind:?}"))
+                                        .to_vec(),
+                                    LevelTransitionKind::ALL
+                                        .iter()
+                                        .position(|&kind| kind == settings.level_transition)
+                                        .unwrap_or_default(),
+                                    on_select_level_transition,
+                                ),
+                            )),
+                            Spawn(widget::setting_row(
+                                "Language",
+                                widget::dropdown(
+                                    Language::ALL
+                                        .map(|language| language.name().to_string())
+                                        .to_vec(),
+                                    Language::ALL
+                                        .iter()
+                                        .position(|&language| language == settings.language)
+                                        .unwrap_or_default(),
+                                    on_select_language,
+                                ),
+                            )),
+                            SpawnIter(key_rows.into_iter()),
+                        )),
+                    )],
+                )),
+                Spawn(widget::menu_button("Back", on_back)),
+            )),
+        )],
+    )
+}
+
+fn show_settings_menu(
+    _trigger: Trigger<ShowSettingsEvent>,
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    rebinding.0 = None;
+    commands.spawn(settings_menu(&settings));
+}
+
+/// Show the bound keys, or which one is being rebound
+fn update_key_binding_texts(
+    mut texts: Query<(&KeyBindingText, &mut Text)>,
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+) {
+    let key_bindings = settings.key_bindings();
+    for (KeyBindingText(key), mut text) in &mut texts {
+        text.0 = if rebinding.0 == Some(*key) {
+            settings.language.translate("Press a key...").to_string()
+        } else {
+            key_name(key_bindings.key_code(*key))
+        };
+    }
+}
+
+/// Bind the next pressed key to the [GameKey] being rebound, Escape cancels
+fn rebind_key(
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut settings: ResMut<Settings>,
+) {
+    let Some(key) = rebinding.0 else {
+        return;
+    };
+    if keyboard.just_pressed(KeyCode::Escape) {
+        rebinding.0 = None;
+    } else if let Some(&key_code) = keyboard
+        .get_just_pressed()
+        .find(|key_code| BINDABLE_KEYS.contains(key_code))
+    {
+        settings.bind(key, key_code);
+        rebinding.0 = None;
+        // The key must not also move the focus or click a button
+        keyboard.clear_just_pressed(key_code);
+    }
+}
+
+fn on_select_display_mode(
+    trigger: Trigger<ValueChangeEvent<usize>>,
+    mut settings: ResMut<Settings>,
+) {
+    settings.display_mode = DisplayMode::ALL[trigger.event().0];
+}
+
+fn on_select_resolution(trigger: Trigger<ValueChangeEvent<usize>>, mut settings: ResMut<Settings>) {
+    settings.resolution = RESOLUTIONS[trigger.event().0];
+}
+
+fn on_toggle_vsync(trigger: Trigger<ValueChangeEvent<bool>>, mut settings: ResMut<Settings>) {
+    settings.vsync = trigger.event().0;
+}
+
+fn on_change_volume(trigger: Trigger<ValueChangeEvent<f32>>, mut settings: ResMut<Settings>) {
+    settings.volume = trigger.event().0;
+}
+
+fn on_toggle_shake(trigger: Trigger<ValueChangeEvent<bool>>, mut settings: ResMut<Settings>) {
+    settings.screen_shake = trigger.event().0;
+}
+
+fn on_toggle_ghost(trigger: Trigger<ValueChangeEvent<bool>>, mut settings: ResMut<Settings>) {
+    settings.ghost = trigger.event().0;
+}
+
+fn on_select_level_transition(
+    trigger: Trigger<ValueChangeEvent<usize>>,
+    mut settings: ResMut<Settings>,
+) {
+    settings.level_transition = LevelTransitionKind::ALL[trigger.event().0];
+}
+
+fn on_select_language(trigger: Trigger<ValueChangeEvent<usize>>, mut settings: ResMut<Settings>) {
+    settings.language = Language::ALL[trigger.event().0];
+}
+
+fn on_back(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    menus: Query<Entity, With<SettingsMenu>>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    rebinding.0 = None;
+    for menu in &menus {
+        commands.entity(menu).despawn();
+    }
+}
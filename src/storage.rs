@@ -0,0 +1,61 @@
+//! Persistence of small data files (personal bests, scores, settings...) in the
+//! user data directory, using the RON format.
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fs, path::PathBuf};
+
+#[cfg(not(test))]
+const APP_DIR: &str = "load-runner";
+
+#[cfg(not(test))]
+fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIR))
+}
+
+/// Tests don't touch the player data
+#[cfg(test)]
+fn data_dir() -> Option<PathBuf> {
+    None
+}
+
+/// Load a value previously [save]d, or its default if there is none.
+///
+/// A file that can't be read anymore is kept aside with a `.corrupted` extension,
+/// so a later [save] doesn't silently erase it.
+pub fn load<T: DeserializeOwned + Default>(file_name: &str) -> T {
+    let Some(path) = data_dir().map(|dir| dir.join(file_name)) else {
+        return T::default();
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return T::default();
+    };
+    match ron::from_str(&content) {
+        Ok(value) => value,
+        Err(e) => {
+            error!("Can't read {}: {e}", path.display());
+            if let Err(e) = fs::rename(&path, path.with_extension("corrupted")) {
+                error!("Can't keep aside {}: {e}", path.display());
+            }
+            T::default()
+        }
+    }
+}
+
+/// Save a value, so it can be [load]ed later.
+///
+/// The value is first written in a temporary file, which then replaces the previous one,
+/// so an interrupted save can't corrupt existing data.
+pub fn save<T: Serialize>(file_name: &str, value: &T) -> Result {
+    let Some(dir) = data_dir() else {
+        warn!("No data directory to save {file_name}");
+        return Ok(());
+    };
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(file_name);
+    let tmp_path = path.with_extension("tmp");
+    let content = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?;
+    fs::write(&tmp_path, content)?;
+    fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
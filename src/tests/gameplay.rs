@@ -0,0 +1,154 @@
+use super::{count, TestGame};
+use crate::{
+    components::{
+        character::Dying,
+        input::GameKey,
+        item::{Chest, Item, Items},
+        level::{Destroyed, Door},
+        player::Player,
+        replay::{Recording, ReplayPlayer},
+        score::Score,
+    },
+    schedule::InGameState,
+};
+use bevy::prelude::*;
+use bevy_ecs_tilemap::tiles::TileVisible;
+
+/// Steps for the player to land on the ground
+const LANDING_STEPS: usize = 30;
+/// Maximum steps to walk to something in the level
+const MAX_WALK_STEPS: usize = 300;
+
+fn player_has(world: &mut World, item: Item) -> bool {
+    world
+        .query_filtered::<&Items, With<Player>>()
+        .iter(world)
+        .any(|items| items.iter().any(|&i| i == item))
+}
+
+/// State of the game compared between a recorded game and its replay
+fn final_state(game: &mut TestGame) -> (InGameState, Vec3, Vec<Item>, u32) {
+    let player = game.player();
+    (
+        game.in_game_state(),
+        game.get::<Transform>(player)
+            .expect("Transform")
+            .translation,
+        game.get::<Items>(player)
+            .expect("Items")
+            .iter()
+            .copied()
+            .collect(),
+        game.app.world().resource::<Score>().points(),
+    )
+}
+
+#[test]
+fn dig_a_hole() {
+    let mut game = TestGame::new("Dig");
+    game.step(LANDING_STEPS);
+    assert_eq!(game.count::<With<Destroyed>>(), 0);
+
+    game.tap(GameKey::DigRight);
+    game.step(1);
+
+    let mut destroyed = game
+        .app
+        .world_mut()
+        .query_filtered::<&TileVisible, With<Destroyed>>();
+    let visibles = destroyed
+        .iter(game.app.world())
+        .map(|visible| visible.0)
+        .collect::<Vec<_>>();
+    assert_eq!(visibles, vec![false]);
+}
+
+#[test]
+fn open_a_chest() {
+    let mut game = TestGame::new("Chest");
+    game.step(LANDING_STEPS);
+    assert!(!player_has(game.app.world_mut(), Item::Gem));
+    assert_eq!(game.count::<With<Chest>>(), 1);
+
+    game.press(GameKey::Right);
+    assert!(game.step_until(MAX_WALK_STEPS, |world| player_has(world, Item::Gem)));
+    game.step(1);
+    assert_eq!(game.count::<With<Chest>>(), 0);
+}
+
+#[test]
+fn open_a_door() {
+    let mut game = TestGame::new("Door");
+    game.step(LANDING_STEPS);
+    assert!(player_has(game.app.world_mut(), Item::Gem));
+
+    game.press(GameKey::Right);
+    assert!(game.step_until(MAX_WALK_STEPS, |world| count::<With<Door>>(world) == 0));
+    assert!(!player_has(game.app.world_mut(), Item::Gem));
+}
+
+#[test]
+fn die_hit_by_an_enemy() {
+    let mut game = TestGame::new("Death");
+    game.step(LANDING_STEPS);
+
+    game.press(GameKey::Right);
+    assert!(game.step_until(MAX_WALK_STEPS, |world| count::<With<Dying>>(world) == 1));
+    let player = game.player();
+    assert!(game.get::<Dying>(player).is_some());
+    game.release(GameKey::Right);
+
+    assert!(game.step_until(MAX_WALK_STEPS, |world| {
+        *world.resource::<State<InGameState>>() == InGameState::PlayerDied
+    }));
+}
+
+#[test]
+fn reach_the_end_of_the_level() {
+    let mut game = TestGame::new("End");
+    game.step(LANDING_STEPS);
+    assert_eq!(game.in_game_state(), InGameState::Running);
+
+    game.press(GameKey::Right);
+    assert!(game.step_until(MAX_WALK_STEPS, |world| {
+        *world.resource::<State<InGameState>>() == InGameState::PlayerEndedLevel
+    }));
+}
+
+#[test]
+fn replay_a_recorded_game() {
+    let mut game = TestGame::new("Chest");
+    game.step(LANDING_STEPS);
+
+    // Open the chest, close its popup and walk to the end of the level
+    game.press(GameKey::Right);
+    assert!(game.step_until(MAX_WALK_STEPS, |world| {
+        *world.resource::<State<InGameState>>() == InGameState::ShowPopup
+    }));
+    game.tap(GameKey::Jump);
+    assert!(game.step_until(MAX_WALK_STEPS, |world| {
+        matches!(
+            world.resource::<NextState<InGameState>>(),
+            NextState::Pending(InGameState::PlayerEndedLevel)
+        )
+    }));
+    // The recording is saved, and taken, when the level ends
+    let replay = game.app.world().resource::<Recording>().0.clone();
+    let replay = replay.expect("Recording");
+    game.step(1);
+    let recorded = final_state(&mut game);
+    assert_eq!(recorded.0, InGameState::PlayerEndedLevel);
+    assert!(recorded.2.contains(&Item::Gem));
+
+    let step_count = replay.step_count();
+    let mut game = TestGame::replay(replay);
+    assert!(game.step_until(MAX_WALK_STEPS * 2, |world| {
+        *world.resource::<State<InGameState>>() == InGameState::PlayerEndedLevel
+            || world.resource::<ReplayPlayer>().finished
+    }));
+    assert_eq!(final_state(&mut game), recorded);
+    assert_eq!(
+        game.app.world().resource::<ReplayPlayer>().steps,
+        step_count
+    );
+}
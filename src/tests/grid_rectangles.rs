@@ -0,0 +1,58 @@
+use crate::utils::grid_rectangles::grid_rectangles;
+use bevy::math::IRect;
+use bevy_ecs_ldtk::prelude::GridCoords;
+use proptest::prelude::*;
+use std::collections::HashSet;
+
+const MAX_SIZE: i32 = 40;
+
+fn rect_cells(rect: &IRect) -> impl Iterator<Item = GridCoords> + '_ {
+    (rect.min.y..=rect.max.y)
+        .flat_map(move |y| (rect.min.x..=rect.max.x).map(move |x| GridCoords { x, y }))
+}
+
+/// A grid size, and cells that may be outside of the grid
+fn grid() -> impl Strategy<Value = (i32, i32, HashSet<GridCoords>)> {
+    (0..MAX_SIZE, 0..MAX_SIZE).prop_flat_map(|(width, height)| {
+        let cell = (-1..=width, -1..=height).prop_map(|(x, y)| GridCoords { x, y });
+        (
+            Just(width),
+            Just(height),
+            prop::collection::hash_set(cell, 0..=((width + 2) * (height + 2)) as usize),
+        )
+    })
+}
+
+proptest! {
+    #[test]
+    fn rectangles_exactly_cover_the_cells((width, height, cells) in grid()) {
+        let rectangles = grid_rectangles(&cells, width, height);
+
+        let mut covered = HashSet::new();
+        for rect in &rectangles {
+            prop_assert!(rect.min.x <= rect.max.x && rect.min.y <= rect.max.y, "{rect:?}");
+            for cell in rect_cells(rect) {
+                prop_assert!(covered.insert(cell), "{cell:?} is overlapped");
+            }
+        }
+        let in_grid = cells
+            .into_iter()
+            .filter(|c| (0..width).contains(&c.x) && (0..height).contains(&c.y))
+            .collect::<HashSet<_>>();
+        prop_assert_eq!(covered, in_grid);
+    }
+
+    #[test]
+    fn full_grid_is_a_single_rectangle(width in 1..MAX_SIZE, height in 1..MAX_SIZE) {
+        let cells = rect_cells(&IRect::new(0, 0, width - 1, height - 1)).collect();
+        prop_assert_eq!(
+            grid_rectangles(&cells, width, height),
+            vec![IRect::new(0, 0, width - 1, height - 1)]
+        );
+    }
+}
+
+#[test]
+fn empty_grid_has_no_rectangle() {
+    assert!(grid_rectangles(&HashSet::new(), 10, 10).is_empty());
+}
@@ -0,0 +1,169 @@
+//! Headless harness to test the gameplay.
+//!
+//! A [TestGame] runs the gameplay plugins without window nor rendering, in a level of
+//! the `tests/gameplay.ldtk` fixture. Each update runs exactly one game step.
+
+mod gameplay;
+mod grid_rectangles;
+mod progressbar;
+
+use crate::{
+    asset_tracking::{asset_tracking_plugin, ResourceHandles},
+    components::{
+        input::GameKey,
+        level::LdtkProjectPath,
+        player::Player,
+        replay::{Replay, ReplayPlayer},
+    },
+    in_game::InGamePlugins,
+    physics_plugin,
+    schedule::{schedule_plugin, GameState, InGameState},
+    ui, utils,
+};
+use bevy::{
+    ecs::query::QueryFilter, image::TextureAtlasPlugin, prelude::*, state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+};
+use bevy_ecs_ldtk::prelude::*;
+use std::time::Duration;
+
+const FIXTURE: &str = "tests/gameplay.ldtk";
+/// Maximum number of updates to load a level
+const MAX_LOADING_UPDATES: usize = 5000;
+
+pub struct TestGame {
+    app: App,
+}
+
+impl TestGame {
+    /// Start a game in a level of the fixture, and wait for the game to run
+    pub fn new(level: &str) -> Self {
+        Self::start(LevelSelection::Identifier(level.into()), None)
+    }
+
+    /// Play a recorded game instead of reading the keyboard
+    pub fn replay(replay: Replay) -> Self {
+        let level_selection = LevelSelection::from(&replay.start_level);
+        Self::start(level_selection, Some(ReplayPlayer::new(replay)))
+    }
+
+    fn start(level_selection: LevelSelection, replay_player: Option<ReplayPlayer>) -> Self {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            ImagePlugin::default_nearest(),
+            TextureAtlasPlugin,
+            StatesPlugin,
+            TransformPlugin,
+            physics_plugin,
+        ))
+        // Each update runs one game step
+        .insert_resource(TimeUpdateStrategy::ManualDuration(
+            Time::<Fixed>::default().timestep(),
+        ))
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_resource::<ClearColor>()
+        .add_plugins((
+            ui::fade::plugin,
+            utils::invulnerable::InvulnerabilityPlugin,
+            utils::despawn_after::despawn_after_plugin,
+            asset_tracking_plugin,
+            schedule_plugin,
+            InGamePlugins,
+        ))
+        .insert_resource(LdtkProjectPath(FIXTURE.into()))
+        .insert_resource(level_selection);
+        if let Some(replay_player) = replay_player {
+            app.insert_resource(replay_player);
+        }
+
+        let mut game = TestGame { app };
+        game.wait_for(|world| world.resource::<ResourceHandles>().is_all_done());
+        game.app
+            .world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::InGame);
+        game.wait_for(|world| *world.resource::<State<InGameState>>() == InGameState::Running);
+        game
+    }
+
+    /// Update until assets are loaded
+    fn wait_for(&mut self, condition: impl Fn(&World) -> bool) {
+        for _ in 0..MAX_LOADING_UPDATES {
+            self.app.update();
+            if condition(self.app.world()) {
+                return;
+            }
+            // Let the asset loading tasks run
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!("Game is not ready after {MAX_LOADING_UPDATES} updates");
+    }
+
+    /// Run game steps
+    pub fn step(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.app.update();
+            // Keys stay pressed, but are not "just pressed" anymore
+            self.keyboard().clear();
+        }
+    }
+
+    /// Run game steps until `condition` is true, returns false if it never happens
+    pub fn step_until(&mut self, max_steps: usize, condition: impl Fn(&mut World) -> bool) -> bool {
+        for _ in 0..max_steps {
+            self.step(1);
+            if condition(self.app.world_mut()) {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn press(&mut self, key: GameKey) {
+        self.keyboard().press(key.key_code());
+    }
+
+    pub fn release(&mut self, key: GameKey) {
+        self.keyboard().release(key.key_code());
+    }
+
+    /// Press a key for a single game step
+    pub fn tap(&mut self, key: GameKey) {
+        self.press(key);
+        self.step(1);
+        self.release(key);
+        self.step(1);
+    }
+
+    fn keyboard(&mut self) -> Mut<ButtonInput<KeyCode>> {
+        self.app.world_mut().resource_mut::<ButtonInput<KeyCode>>()
+    }
+
+    pub fn in_game_state(&self) -> InGameState {
+        *self.app.world().resource::<State<InGameState>>().get()
+    }
+
+    pub fn player(&mut self) -> Entity {
+        self.app
+            .world_mut()
+            .query_filtered::<Entity, With<Player>>()
+            .single(self.app.world())
+            .expect("Player")
+    }
+
+    pub fn get<C: Component>(&self, entity: Entity) -> Option<&C> {
+        self.app.world().get::<C>(entity)
+    }
+
+    /// Number of entities matching a filter
+    pub fn count<F: QueryFilter>(&mut self) -> usize {
+        count::<F>(self.app.world_mut())
+    }
+}
+
+/// Number of entities matching a filter, to use in [TestGame::step_until] conditions
+pub fn count<F: QueryFilter>(world: &mut World) -> usize {
+    world.query_filtered::<(), F>().iter(world).count()
+}
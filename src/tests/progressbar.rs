@@ -0,0 +1,30 @@
+use crate::ui::progressbar::ProgressBar;
+
+#[test]
+fn percent_is_relative_to_min() {
+    assert_eq!(ProgressBar::new(10., 20., 15.).percent(), 0.5);
+    assert_eq!(ProgressBar::new(10., 20., 10.).percent(), 0.);
+    assert_eq!(ProgressBar::new(10., 20., 20.).percent(), 1.);
+}
+
+#[test]
+fn percent_is_clamped() {
+    assert_eq!(ProgressBar::new(10., 20., 5.).percent(), 0.);
+    assert_eq!(ProgressBar::new(10., 20., 25.).percent(), 1.);
+}
+
+#[test]
+fn percent_of_an_empty_range_is_zero() {
+    assert_eq!(ProgressBar::new(10., 10., 10.).percent(), 0.);
+    assert_eq!(ProgressBar::new(20., 10., 15.).percent(), 0.);
+}
+
+#[test]
+fn set_range_changes_percent() {
+    let mut bar = ProgressBar::new(0., 10., 5.);
+    bar.set_range(4., 8.);
+    assert_eq!((bar.min, bar.max), (4., 8.));
+    assert_eq!(bar.percent(), 0.25);
+    bar.set_range(5., 5.);
+    assert_eq!(bar.percent(), 0.);
+}
@@ -0,0 +1,377 @@
+//! Stateful widgets: checkboxes, sliders, dropdowns and scrollable lists.
+//!
+//! They keep their value in their component, and trigger a [ValueChangeEvent] on themselves when
+//! the player changes it, with the mouse, the keyboard or a gamepad.
+
+use super::{
+    focus::{FocusSystems, Focusable, FocusedWidget, NavigationInput},
+    palette::*,
+};
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    ui::RelativeCursorPosition,
+};
+
+/// Height of a line scrolled by the mouse wheel
+const SCROLL_LINE_HEIGHT: f32 = 24.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Checkbox>()
+        .register_type::<Slider>()
+        .register_type::<Dropdown>()
+        .add_systems(
+            Update,
+            (
+                (drag_sliders, adjust_focused_control, scroll_lists).after(FocusSystems),
+                (update_checkbox_marks, update_slider_fills, update_dropdowns)
+                    .after(adjust_focused_control)
+                    .after(drag_sliders),
+                scroll_to_focused_widget.after(FocusSystems),
+            ),
+        );
+}
+
+/// Triggered on a control when the player changes its value
+#[derive(Event, Debug, Clone)]
+pub struct ValueChangeEvent<T>(pub T);
+
+/// A box that is checked or not, flipped by a click
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+#[require(Focusable)]
+pub struct Checkbox {
+    pub checked: bool,
+}
+
+/// The mark shown in a checked [Checkbox]
+#[derive(Component)]
+pub(super) struct CheckboxMark;
+
+/// A value in a range, chosen by clicking or dragging along a track
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+#[require(Focusable, RelativeCursorPosition)]
+pub struct Slider {
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+    /// Change of the value with the keyboard, and precision of the value
+    pub step: f32,
+}
+
+impl Slider {
+    /// Position of the value in the range, from 0 to 1
+    pub fn fraction(&self) -> f32 {
+        if self.max > self.min {
+            ((self.value - self.min) / (self.max - self.min)).clamp(0., 1.)
+        } else {
+            0.
+        }
+    }
+
+    /// The nearest valid value
+    fn snap(&self, value: f32) -> f32 {
+        let value = if self.step > 0. {
+            self.min + ((value - self.min) / self.step).round() * self.step
+        } else {
+            value
+        };
+        value.clamp(self.min, self.max)
+    }
+}
+
+/// The part of a [Slider] track filled up to its value
+#[derive(Component)]
+pub(super) struct SliderFill;
+
+/// A choice between options, showing the selected one and listing all of them when clicked
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+#[require(Focusable)]
+pub struct Dropdown {
+    pub options: Vec<String>,
+    pub selected: usize,
+    /// The list of options, when open
+    list: Option<Entity>,
+}
+
+impl Dropdown {
+    pub fn new(options: Vec<String>, selected: usize) -> Self {
+        Dropdown {
+            options,
+            selected,
+            list: None,
+        }
+    }
+
+    fn selected_text(&self) -> &str {
+        self.options.get(self.selected).map_or("", String::as_str)
+    }
+}
+
+/// The text of a [Dropdown] showing the selected option
+#[derive(Component)]
+pub(super) struct DropdownText;
+
+/// An option in the open list of a [Dropdown]
+#[derive(Component)]
+#[require(Focusable)]
+struct DropdownOption {
+    dropdown: Entity,
+    index: usize,
+}
+
+/// A vertical list scrolled with the mouse wheel, and to show the focused widget
+#[derive(Component, Default)]
+#[require(RelativeCursorPosition, ScrollPosition)]
+pub struct ScrollList;
+
+pub(super) fn toggle_checkbox(
+    trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut checkboxes: Query<&mut Checkbox>,
+) {
+    let entity = trigger.target();
+    if let Ok(mut checkbox) = checkboxes.get_mut(entity) {
+        checkbox.checked = !checkbox.checked;
+        commands.trigger_targets(ValueChangeEvent(checkbox.checked), entity);
+    }
+}
+
+pub(super) fn toggle_dropdown(
+    trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut dropdowns: Query<&mut Dropdown>,
+) {
+    let entity = trigger.target();
+    let Ok(mut dropdown) = dropdowns.get_mut(entity) else {
+        return;
+    };
+    if let Some(list) = dropdown.list.take() {
+        commands.entity(list).despawn();
+        return;
+    }
+    let options = dropdown
+        .options
+        .iter()
+        .enumerate()
+        .map(|(index, option)| dropdown_option(entity, index, option.clone()))
+        .collect::<Vec<_>>();
+    let list = commands
+        .spawn((
+            Name::new("DropdownList"),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(100.0),
+                left: Val::Px(0.0),
+                min_width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                border: UiRect::all(Val::Px(2.0)),
+                ..default()
+            },
+            BackgroundColor(POPUP_BACKGROUND),
+            BorderColor(POPUP_BORDER),
+            // Above the widgets spawned after the dropdown
+            GlobalZIndex(10),
+            ChildOf(entity),
+        ))
+        .with_children(|parent| {
+            for option in options {
+                parent.spawn(option).observe(select_dropdown_option);
+            }
+        })
+        .id();
+    dropdown.list = Some(list);
+}
+
+fn dropdown_option(dropdown: Entity, index: usize, text: String) -> impl Bundle {
+    (
+        Name::new("DropdownOption"),
+        DropdownOption { dropdown, index },
+        Button,
+        Node {
+            padding: UiRect::axes(Val::Px(12.0), Val::Px(4.0)),
+            ..default()
+        },
+        BackgroundColor(MENU_BUTTON_PALETTE.interaction.none),
+        MENU_BUTTON_PALETTE.interaction,
+        children![(
+            Text(text),
+            TextFont::from_font_size(MENU_BUTTON_PALETTE.text_size),
+            TextColor(MENU_BUTTON_PALETTE.text_color),
+            Pickable::IGNORE,
+        )],
+    )
+}
+
+fn select_dropdown_option(
+    mut trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    options: Query<&DropdownOption>,
+    mut dropdowns: Query<&mut Dropdown>,
+) {
+    // The click must not reopen the dropdown
+    trigger.propagate(false);
+    let Ok(option) = options.get(trigger.target()) else {
+        return;
+    };
+    let Ok(mut dropdown) = dropdowns.get_mut(option.dropdown) else {
+        return;
+    };
+    if let Some(list) = dropdown.list.take() {
+        commands.entity(list).despawn();
+    }
+    if dropdown.selected != option.index {
+        dropdown.selected = option.index;
+        commands.trigger_targets(ValueChangeEvent(option.index), option.dropdown);
+    }
+}
+
+/// Set the value of a pressed [Slider] from the cursor position along its track
+fn drag_sliders(
+    mut commands: Commands,
+    mut sliders: Query<(Entity, &mut Slider, &Interaction, &RelativeCursorPosition)>,
+) {
+    for (entity, mut slider, interaction, cursor) in &mut sliders {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(cursor) = cursor.normalized else {
+            continue;
+        };
+        let value = slider.snap(slider.min + cursor.x * (slider.max - slider.min));
+        if value != slider.value {
+            slider.value = value;
+            commands.trigger_targets(ValueChangeEvent(value), entity);
+        }
+    }
+}
+
+/// Change the value of the focused [Slider] or [Dropdown] with left and right
+fn adjust_focused_control(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    focused: Res<FocusedWidget>,
+    mut sliders: Query<&mut Slider>,
+    mut dropdowns: Query<&mut Dropdown>,
+) {
+    let Some(entity) = focused.0 else {
+        return;
+    };
+    let direction = match NavigationInput::read(&keyboard, &gamepads) {
+        Some(NavigationInput::Decrease) => -1,
+        Some(NavigationInput::Increase) => 1,
+        _ => return,
+    };
+    if let Ok(mut slider) = sliders.get_mut(entity) {
+        let value = slider.snap(slider.value + direction as f32 * slider.step);
+        if value != slider.value {
+            slider.value = value;
+            commands.trigger_targets(ValueChangeEvent(value), entity);
+        }
+    } else if let Ok(mut dropdown) = dropdowns.get_mut(entity) {
+        if dropdown.options.is_empty() {
+            return;
+        }
+        let count = dropdown.options.len() as isize;
+        let selected = (dropdown.selected as isize + direction).rem_euclid(count) as usize;
+        dropdown.selected = selected;
+        commands.trigger_targets(ValueChangeEvent(selected), entity);
+    }
+}
+
+fn update_checkbox_marks(
+    checkboxes: Query<(&Checkbox, &Children), Changed<Checkbox>>,
+    mut marks: Query<&mut Visibility, With<CheckboxMark>>,
+) {
+    for (checkbox, children) in &checkboxes {
+        let mut marks = marks.iter_many_mut(children);
+        while let Some(mut visibility) = marks.fetch_next() {
+            *visibility = if checkbox.checked {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
+    }
+}
+
+fn update_slider_fills(
+    sliders: Query<(&Slider, &Children), Changed<Slider>>,
+    mut fills: Query<&mut Node, With<SliderFill>>,
+) {
+    for (slider, children) in &sliders {
+        let mut fills = fills.iter_many_mut(children);
+        while let Some(mut node) = fills.fetch_next() {
+            node.width = Val::Percent(slider.fraction() * 100.);
+        }
+    }
+}
+
+fn update_dropdowns(
+    dropdowns: Query<(&Dropdown, &Children), Changed<Dropdown>>,
+    mut texts: Query<&mut Text, With<DropdownText>>,
+) {
+    for (dropdown, children) in &dropdowns {
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.0 = format!("{} v", dropdown.selected_text());
+        }
+    }
+}
+
+/// Scroll the hovered [ScrollList] with the mouse wheel
+fn scroll_lists(
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    mut lists: Query<(&mut ScrollPosition, &RelativeCursorPosition), With<ScrollList>>,
+) {
+    for event in mouse_wheel_events.read() {
+        let dy = match event.unit {
+            MouseScrollUnit::Line => event.y * SCROLL_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => event.y,
+        };
+        for (mut scroll_position, cursor) in &mut lists {
+            if cursor.mouse_over() {
+                scroll_position.offset_y -= dy;
+            }
+        }
+    }
+}
+
+/// Scroll the [ScrollList] containing the focused widget until it is visible
+fn scroll_to_focused_widget(
+    focused: Res<FocusedWidget>,
+    parents: Query<&ChildOf>,
+    nodes: Query<(&ComputedNode, &GlobalTransform)>,
+    mut lists: Query<(&mut ScrollPosition, &ComputedNode, &GlobalTransform), With<ScrollList>>,
+) {
+    if !focused.is_changed() {
+        return;
+    }
+    let Some(entity) = focused.0 else {
+        return;
+    };
+    let Some(list) = parents
+        .iter_ancestors(entity)
+        .find(|&ancestor| lists.contains(ancestor))
+    else {
+        return;
+    };
+    let (Ok((node, transform)), Ok((mut scroll_position, list_node, list_transform))) =
+        (nodes.get(entity), lists.get_mut(list))
+    else {
+        return;
+    };
+    // In physical pixels, going down
+    let top = transform.translation().y - node.size().y / 2.;
+    let bottom = transform.translation().y + node.size().y / 2.;
+    let list_top = list_transform.translation().y - list_node.size().y / 2.;
+    let list_bottom = list_transform.translation().y + list_node.size().y / 2.;
+    if top < list_top {
+        scroll_position.offset_y -= (list_top - top) * list_node.inverse_scale_factor();
+    } else if bottom > list_bottom {
+        scroll_position.offset_y += (bottom - list_bottom) * list_node.inverse_scale_factor();
+    }
+}
@@ -0,0 +1,181 @@
+//! Keyboard and gamepad navigation between the widgets of the front-most menu.
+
+use bevy::{
+    picking::{
+        backend::HitData,
+        pointer::{Location, PointerButton, PointerId},
+    },
+    prelude::*,
+    render::camera::NormalizedRenderTarget,
+    window::{PrimaryWindow, WindowRef},
+};
+use std::time::Duration;
+
+use super::palette::LABEL_TEXT;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<FocusedWidget>().add_systems(
+        Update,
+        (navigate_focus, activate_focused_widget, show_focus)
+            .chain()
+            .in_set(FocusSystems),
+    );
+}
+
+/// Systems moving the focus and activating the focused widget
+#[derive(SystemSet, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct FocusSystems;
+
+/// A widget that can get the focus, to be used with the keyboard or a gamepad
+#[derive(Component, Default)]
+pub struct Focusable;
+
+/// The [Focusable] widget receiving the keyboard and gamepad inputs
+#[derive(Resource, Default, Debug)]
+pub struct FocusedWidget(pub Option<Entity>);
+
+/// Direction pressed on the keyboard or a gamepad, to move in menus
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NavigationInput {
+    Previous,
+    Next,
+    Decrease,
+    Increase,
+    Activate,
+}
+
+impl NavigationInput {
+    /// The navigation just pressed, if any
+    pub fn read(keyboard: &ButtonInput<KeyCode>, gamepads: &Query<&Gamepad>) -> Option<Self> {
+        let shift = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        let key = |key_code| keyboard.just_pressed(key_code);
+        let button = |button| gamepads.iter().any(|gamepad| gamepad.just_pressed(button));
+        if key(KeyCode::ArrowUp) || (shift && key(KeyCode::Tab)) || button(GamepadButton::DPadUp) {
+            Some(NavigationInput::Previous)
+        } else if key(KeyCode::ArrowDown) || key(KeyCode::Tab) || button(GamepadButton::DPadDown) {
+            Some(NavigationInput::Next)
+        } else if key(KeyCode::ArrowLeft) || button(GamepadButton::DPadLeft) {
+            Some(NavigationInput::Decrease)
+        } else if key(KeyCode::ArrowRight) || button(GamepadButton::DPadRight) {
+            Some(NavigationInput::Increase)
+        } else if key(KeyCode::Enter) || button(GamepadButton::South) {
+            Some(NavigationInput::Activate)
+        } else {
+            None
+        }
+    }
+}
+
+/// Move the focus up and down between the visible [Focusable]s of the menu drawn on top,
+/// from the top left to the bottom right
+fn navigate_focus(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut focused: ResMut<FocusedWidget>,
+    focusables: Query<(Entity, &GlobalTransform, &InheritedVisibility), With<Focusable>>,
+    parents: Query<&ChildOf>,
+    z_indices: Query<&GlobalZIndex>,
+) {
+    if focused.0.is_some_and(|entity| !focusables.contains(entity)) {
+        focused.0 = None;
+    }
+    let step = match NavigationInput::read(&keyboard, &gamepads) {
+        Some(NavigationInput::Previous) => -1,
+        Some(NavigationInput::Next) => 1,
+        _ => return,
+    };
+
+    let menu_z_index = |entity| {
+        let root = parents.root_ancestor(entity);
+        z_indices.get(root).map_or(0, |z_index| z_index.0)
+    };
+    let mut candidates = focusables
+        .iter()
+        .filter(|(_, _, visibility)| visibility.get())
+        .map(|(entity, transform, _)| (entity, transform.translation().xy(), menu_z_index(entity)))
+        .collect::<Vec<_>>();
+    let Some(top_z_index) = candidates.iter().map(|(_, _, z_index)| *z_index).max() else {
+        return;
+    };
+    candidates.retain(|(_, _, z_index)| *z_index == top_z_index);
+    // UI coordinates go down
+    candidates.sort_by(|(_, a, _), (_, b, _)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    let current = focused.0.and_then(|entity| {
+        candidates
+            .iter()
+            .position(|(candidate, _, _)| *candidate == entity)
+    });
+    let next = match current {
+        Some(index) => (index as isize + step).rem_euclid(candidates.len() as isize) as usize,
+        None if step > 0 => 0,
+        None => candidates.len() - 1,
+    };
+    focused.0 = Some(candidates[next].0);
+}
+
+/// Click on the focused widget, so it runs the same actions as with a mouse
+fn activate_focused_widget(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    focused: Res<FocusedWidget>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    cameras: Query<Entity, With<Camera>>,
+) {
+    let Some(entity) = focused.0 else {
+        return;
+    };
+    if NavigationInput::read(&keyboard, &gamepads) != Some(NavigationInput::Activate) {
+        return;
+    }
+    let (Some(window), Some(camera)) = (
+        windows
+            .single()
+            .ok()
+            .and_then(|window| WindowRef::Primary.normalize(Some(window))),
+        cameras.iter().next(),
+    ) else {
+        return;
+    };
+    let click = Click {
+        button: PointerButton::Primary,
+        hit: HitData {
+            camera,
+            depth: 0.,
+            position: None,
+            normal: None,
+        },
+        duration: Duration::ZERO,
+    };
+    let location = Location {
+        target: NormalizedRenderTarget::Window(window),
+        position: Vec2::ZERO,
+    };
+    commands.trigger_targets(
+        Pointer::new(PointerId::Mouse, location, entity, click),
+        entity,
+    );
+}
+
+/// Outline the focused widget
+fn show_focus(
+    mut commands: Commands,
+    focused: Res<FocusedWidget>,
+    mut previous: Local<Option<Entity>>,
+) {
+    if !focused.is_changed() || *previous == focused.0 {
+        return;
+    }
+    if let Some(entity) = previous.take() {
+        commands.entity(entity).try_remove::<Outline>();
+    }
+    if let Some(entity) = focused.0 {
+        commands.entity(entity).insert(Outline {
+            width: Val::Px(2.0),
+            offset: Val::Px(2.0),
+            color: LABEL_TEXT,
+        });
+    }
+    *previous = focused.0;
+}
@@ -1,10 +1,18 @@
 // Unused utilities may trigger this lints undesirably.
 #![allow(dead_code)]
 
+pub mod controls;
+pub mod focus;
 pub mod interaction;
 pub mod palette;
+pub mod text_input;
 pub mod widget;
 
 pub fn theme_plugin(app: &mut bevy::app::App) {
-    app.add_plugins(interaction::plugin);
+    app.add_plugins((
+        focus::plugin,
+        controls::plugin,
+        interaction::plugin,
+        text_input::plugin,
+    ));
 }
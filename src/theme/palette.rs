@@ -38,5 +38,9 @@ pub const BUTTON_HOVERED_BACKGROUND: Color = Color::srgb(0.384, 0.600, 0.820);
 /// #3d4999
 pub const BUTTON_PRESSED_BACKGROUND: Color = Color::srgb(0.239, 0.286, 0.600);
 
+/// Text input
+pub const TEXT_INPUT_BACKGROUND: Color = Color::srgb_u8(27, 35, 46);
+pub const TEXT_INPUT_TEXT: Color = Color::srgb(0.867, 0.827, 0.412);
+
 pub const POPUP_BACKGROUND: Color = Color::srgb(0.25, 0.25, 0.25);
 pub const POPUP_BORDER: Color = Color::BLACK;
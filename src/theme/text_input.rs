@@ -0,0 +1,97 @@
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+
+use super::focus::FocusedWidget;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<TextInput>()
+        .add_systems(Update, (edit_text_inputs, update_text_input_texts).chain())
+        .add_observer(focus_clicked_text_input);
+}
+
+/// A single line text, edited with the keyboard.
+///
+/// Its text is displayed by the [`Text`] of its children.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct TextInput {
+    pub value: String,
+    /// Maximum number of characters
+    pub max_len: usize,
+}
+
+/// Triggered on a [`TextInput`] when Enter is pressed
+#[derive(Event, Debug)]
+pub struct TextInputSubmitEvent(pub String);
+
+fn edit_text_inputs(
+    mut commands: Commands,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut text_inputs: Query<(Entity, &mut TextInput)>,
+    focused: Res<FocusedWidget>,
+) {
+    // Without a focused text input, the keys go to all of them
+    let focused_input = focused.0.filter(|&entity| text_inputs.contains(entity));
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        for (entity, mut text_input) in &mut text_inputs {
+            if focused_input.is_some_and(|focused_input| focused_input != entity) {
+                continue;
+            }
+            match &event.logical_key {
+                Key::Character(chars) => {
+                    for c in chars.chars().filter(|c| !c.is_control()) {
+                        if text_input.value.chars().count() < text_input.max_len {
+                            text_input.value.push(c);
+                        }
+                    }
+                }
+                Key::Space => {
+                    if text_input.value.chars().count() < text_input.max_len {
+                        text_input.value.push(' ');
+                    }
+                }
+                Key::Backspace => {
+                    text_input.value.pop();
+                }
+                Key::Enter => {
+                    commands
+                        .trigger_targets(TextInputSubmitEvent(text_input.value.clone()), entity);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn focus_clicked_text_input(
+    trigger: Trigger<Pointer<Click>>,
+    text_inputs: Query<(), With<TextInput>>,
+    mut focused: ResMut<FocusedWidget>,
+) {
+    let entity = trigger.target();
+    if text_inputs.contains(entity) && focused.0 != Some(entity) {
+        focused.0 = Some(entity);
+    }
+}
+
+fn update_text_input_texts(
+    text_inputs: Query<(&TextInput, &Children), Changed<TextInput>>,
+    mut texts: Query<&mut Text>,
+) {
+    for (text_input, children) in &text_inputs {
+        for &child in children {
+            if let Ok(mut text) = texts.get_mut(child) {
+                // Show a cursor at the end of the text
+                text.0 = format!("{}_", text_input.value);
+            }
+        }
+    }
+}
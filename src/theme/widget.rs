@@ -1,6 +1,17 @@
 //! Helper functions for creating common widgets.
 
-use crate::theme::palette::*;
+use crate::{
+    localization::Localized,
+    theme::{
+        controls::{
+            self, Checkbox, CheckboxMark, Dropdown, DropdownText, ScrollList, Slider, SliderFill,
+            ValueChangeEvent,
+        },
+        focus::Focusable,
+        palette::*,
+        text_input::TextInput,
+    },
+};
 use bevy::{
     ecs::{spawn::SpawnWith, system::IntoObserverSystem},
     prelude::*,
@@ -52,12 +63,17 @@ pub fn ui_root(name: impl Into<Cow<'static, str>>) -> impl Bundle {
 
 /// A simple popup
 pub fn popup() -> impl Bundle {
+    popup_sized(Val::Percent(40.0))
+}
+
+/// A [popup] of a given width
+pub fn popup_sized(width: Val) -> impl Bundle {
     (
         Node {
             flex_direction: FlexDirection::Column,
             align_items: AlignItems::Center,
             border: UiRect::all(Val::Px(2.0)),
-            width: Val::Percent(40.0),
+            width,
             margin: UiRect::all(Val::Auto),
             padding: UiRect::bottom(Val::Px(7.0)),
             ..Default::default()
@@ -70,6 +86,7 @@ pub fn popup() -> impl Bundle {
 
 /// The title of a [popup]
 pub fn popup_title(title: impl Into<String>) -> impl Bundle {
+    let title = title.into();
     (
         Name::new("PopupTitle"),
         Node {
@@ -79,7 +96,7 @@ pub fn popup_title(title: impl Into<String>) -> impl Bundle {
         },
         BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
         children![
-            Text(title.into()),
+            (Text(title.clone()), Localized(title)),
             TextFont::from_font_size(32.),
             TextColor(Color::srgb(0.72, 0.72, 0.72))
         ],
@@ -88,9 +105,11 @@ pub fn popup_title(title: impl Into<String>) -> impl Bundle {
 
 /// A simple header label. Bigger than [`label`].
 pub fn header(text: impl Into<String>) -> impl Bundle {
+    let text = text.into();
     (
         Name::new("Header"),
-        Text(text.into()),
+        Text(text.clone()),
+        Localized(text),
         TextFont::from_font_size(48.0),
         TextColor(HEADER_TEXT),
     )
@@ -106,6 +125,41 @@ pub fn label(text: impl Into<String>) -> impl Bundle {
     )
 }
 
+/// A [`label`] of a fixed English text, shown in the language of the settings.
+pub fn localized_label(text: impl Into<String>) -> impl Bundle {
+    let text = text.into();
+    (label(text.clone()), Localized(text))
+}
+
+/// A single line text input, that triggers a
+/// [`TextInputSubmitEvent`](crate::theme::text_input::TextInputSubmitEvent) when Enter is pressed.
+pub fn text_input(value: impl Into<String>, max_len: usize) -> impl Bundle {
+    (
+        Name::new("TextInput"),
+        TextInput {
+            value: value.into(),
+            max_len,
+        },
+        Focusable,
+        Node {
+            width: Val::Px(300.0),
+            height: Val::Px(40.0),
+            align_items: AlignItems::Center,
+            border: UiRect::all(Val::Px(2.0)),
+            padding: UiRect::horizontal(Val::Px(8.0)),
+            ..default()
+        },
+        BackgroundColor(TEXT_INPUT_BACKGROUND),
+        BorderColor(Color::BLACK),
+        children![(
+            Text::default(),
+            TextFont::from_font_size(24.0),
+            TextColor(TEXT_INPUT_TEXT),
+            Pickable::IGNORE,
+        )],
+    )
+}
+
 /// A large rounded button with text and an action defined as an [`Observer`].
 pub fn menu_button<E, B, M, I>(text: impl Into<String>, action: I) -> impl Bundle
 where
@@ -130,25 +184,174 @@ where
     )
 }
 
-// /// A small square button with text and an action defined as an [`Observer`].
-// pub fn button_small<E, B, M, I>(text: impl Into<String>, action: I) -> impl Bundle
-// where
-//     E: Event,
-//     B: Bundle,
-//     I: IntoObserverSystem<E, B, M>,
-// {
-//     button_base(
-//         text,
-//         action,
-//         Node {
-//             width: Val::Px(30.0),
-//             height: Val::Px(30.0),
-//             align_items: AlignItems::Center,
-//             justify_content: JustifyContent::Center,
-//             ..default()
-//         },
-//     )
-// }
+/// A small rounded button with text and an action defined as an [`Observer`].
+pub fn button_small<E, B, M, I>(text: impl Into<String>, action: I) -> impl Bundle
+where
+    E: Event,
+    B: Bundle,
+    I: IntoObserverSystem<E, B, M>,
+{
+    button_base(
+        text,
+        MENU_BUTTON_PALETTE,
+        action,
+        (
+            Node {
+                min_width: Val::Px(40.0),
+                height: Val::Px(36.0),
+                padding: UiRect::horizontal(Val::Px(12.0)),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BorderRadius::MAX,
+        ),
+    )
+}
+
+/// A labelled line of a settings screen, with its control on the right
+pub fn setting_row(label_text: impl Into<String>, control: impl Bundle) -> impl Bundle {
+    (
+        Name::new("SettingRow"),
+        Node {
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::SpaceBetween,
+            column_gap: Val::Px(20.0),
+            ..hsizer()
+        },
+        children![localized_label(label_text), control],
+    )
+}
+
+/// A [`Checkbox`], with an action run when it is flipped
+pub fn checkbox<B, M, I>(checked: bool, action: I) -> impl Bundle
+where
+    B: Bundle,
+    I: IntoObserverSystem<ValueChangeEvent<bool>, B, M>,
+{
+    let action = IntoObserverSystem::into_system(action);
+    (
+        Name::new("Checkbox"),
+        Node::default(),
+        Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+            parent
+                .spawn((
+                    Checkbox { checked },
+                    Button,
+                    Node {
+                        width: Val::Px(36.0),
+                        height: Val::Px(36.0),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..centered()
+                    },
+                    BackgroundColor(MENU_BUTTON_PALETTE.interaction.none),
+                    MENU_BUTTON_PALETTE.interaction,
+                    BorderColor(Color::BLACK),
+                    BorderRadius::all(Val::Px(6.0)),
+                    children![(
+                        CheckboxMark,
+                        Text::new("X"),
+                        TextFont::from_font_size(MENU_BUTTON_PALETTE.text_size),
+                        TextColor(MENU_BUTTON_PALETTE.text_color),
+                        Pickable::IGNORE,
+                    )],
+                ))
+                .observe(controls::toggle_checkbox)
+                .observe(action);
+        })),
+    )
+}
+
+/// A [`Slider`] track, with an action run when its value changes
+pub fn slider<B, M, I>(slider: Slider, action: I) -> impl Bundle
+where
+    B: Bundle,
+    I: IntoObserverSystem<ValueChangeEvent<f32>, B, M>,
+{
+    let action = IntoObserverSystem::into_system(action);
+    (
+        Name::new("Slider"),
+        Node::default(),
+        Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+            parent
+                .spawn((
+                    slider,
+                    Button,
+                    Node {
+                        width: Val::Px(160.0),
+                        height: Val::Px(20.0),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    BackgroundColor(TEXT_INPUT_BACKGROUND),
+                    BorderColor(Color::BLACK),
+                    children![(
+                        SliderFill,
+                        Node {
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(LABEL_TEXT),
+                        Pickable::IGNORE,
+                    )],
+                ))
+                .observe(action);
+        })),
+    )
+}
+
+/// A [`Dropdown`] of `options`, with an action run when another option is selected
+pub fn dropdown<B, M, I>(options: Vec<String>, selected: usize, action: I) -> impl Bundle
+where
+    B: Bundle,
+    I: IntoObserverSystem<ValueChangeEvent<usize>, B, M>,
+{
+    let action = IntoObserverSystem::into_system(action);
+    (
+        Name::new("Dropdown"),
+        Node::default(),
+        Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+            parent
+                .spawn((
+                    Dropdown::new(options, selected),
+                    Button,
+                    Node {
+                        min_width: Val::Px(200.0),
+                        height: Val::Px(36.0),
+                        padding: UiRect::horizontal(Val::Px(12.0)),
+                        ..centered()
+                    },
+                    BackgroundColor(MENU_BUTTON_PALETTE.interaction.none),
+                    MENU_BUTTON_PALETTE.interaction,
+                    BorderRadius::all(Val::Px(6.0)),
+                    children![(
+                        DropdownText,
+                        Text::default(),
+                        TextFont::from_font_size(MENU_BUTTON_PALETTE.text_size),
+                        TextColor(MENU_BUTTON_PALETTE.text_color),
+                        Pickable::IGNORE,
+                    )],
+                ))
+                .observe(controls::toggle_dropdown)
+                .observe(action);
+        })),
+    )
+}
+
+/// A [`ScrollList`] of rows, scrolled when higher than `max_height`
+pub fn scroll_list(max_height: Val) -> impl Bundle {
+    (
+        Name::new("ScrollList"),
+        ScrollList,
+        Node {
+            width: Val::Percent(100.0),
+            max_height,
+            row_gap: Val::Px(6.0),
+            overflow: Overflow::scroll_y(),
+            ..vsizer()
+        },
+    )
+}
 
 /// A simple button with text and an action defined as an [`Observer`].
 /// The button's layout is provided by `button_bundle`.
@@ -172,10 +375,12 @@ where
             parent
                 .spawn((
                     Button,
+                    Focusable,
                     BackgroundColor(palette.interaction.none),
                     palette.interaction,
                     children![(
-                        Text(text),
+                        Text(text.clone()),
+                        Localized(text),
                         TextFont::from_font_size(palette.text_size),
                         TextColor(palette.text_color),
                         // Don't bubble picking events from the text up to the button.
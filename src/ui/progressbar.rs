@@ -1,29 +1,65 @@
-use std::sync::{Arc, Mutex};
-
 use bevy::prelude::*;
 
 pub fn plugin(app: &mut App) {
-    app.add_systems(Update, (create_progress_bars, update_progress_bars));
+    app.register_type::<ProgressBar>().add_systems(
+        Update,
+        (
+            layout_progress_bars,
+            move_progress_bar_trails,
+            update_progress_bar_segments,
+            update_progress_bar_fills,
+            update_progress_bar_labels,
+        )
+            .chain(),
+    );
+}
+
+/// How fast the damage trail catches up with the value, per second
+const TRAIL_RATE: f32 = 4.0;
+/// Space between the segments of a segmented bar
+const SEGMENT_GAP: Val = Val::Px(2.0);
+
+/// Direction in which a [ProgressBar] fills up
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Reflect)]
+pub enum BarOrientation {
+    /// From left to right
+    #[default]
+    Horizontal,
+    /// From bottom to top
+    Vertical,
 }
 
-/// The [ProgressBar] component should be nested with a bevy Node
-#[derive(Debug, Clone, Component)]
+/// A bar showing a value between `min` and `max`, drawn in its [Node]
+#[derive(Debug, Clone, Component, Reflect)]
+#[reflect(Component)]
+#[require(Node, TrailPercent)]
 pub struct ProgressBar {
+    pub min: f32,
+    pub max: f32,
+    pub value: f32,
     pub foreground: Color,
     pub background: Color,
-    min: Arc<Mutex<f32>>,
-    max: Arc<Mutex<f32>>,
-    value: Arc<Mutex<f32>>,
+    pub orientation: BarOrientation,
+    /// Color of the part just lost, that shrinks smoothly down to the value
+    pub trail: Option<Color>,
+    /// Show the value and the maximum over the bar
+    pub label: bool,
+    /// Number of separate segments, like hearts, or 1 for a continuous bar
+    pub segments: u32,
 }
 
 impl ProgressBar {
     pub fn new(min: f32, max: f32, value: f32) -> Self {
         ProgressBar {
-            min: Arc::new(Mutex::new(min)),
-            max: Arc::new(Mutex::new(max)),
-            value: Arc::new(Mutex::new(value)),
+            min,
+            max,
+            value,
             foreground: Color::WHITE,
             background: Color::BLACK,
+            orientation: BarOrientation::Horizontal,
+            trail: None,
+            label: false,
+            segments: 1,
         }
     }
 
@@ -33,63 +69,232 @@ impl ProgressBar {
         self
     }
 
+    pub fn with_orientation(mut self, orientation: BarOrientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    pub fn with_trail(mut self, color: Color) -> Self {
+        self.trail = Some(color);
+        self
+    }
+
+    pub fn with_label(mut self) -> Self {
+        self.label = true;
+        self
+    }
+
+    pub fn with_segments(mut self, segments: u32) -> Self {
+        self.segments = segments;
+        self
+    }
+
     pub fn set_value(&mut self, value: f32) {
-        if let Ok(mut store) = self.value.lock() {
-            *store = value;
+        self.value = value;
+    }
+
+    pub fn set_range(&mut self, min: f32, max: f32) {
+        self.min = min;
+        self.max = max;
+    }
+
+    /// Position of the value between min and max, from 0 to 1
+    pub fn percent(&self) -> f32 {
+        if self.max > self.min {
+            ((self.value - self.min) / (self.max - self.min)).clamp(0., 1.)
+        } else {
+            0.
+        }
+    }
+
+    fn segment_count(&self) -> u32 {
+        self.segments.max(1)
+    }
+
+    fn layout(&self) -> ProgressBarLayout {
+        ProgressBarLayout {
+            orientation: self.orientation,
+            segments: self.segment_count(),
+            label: self.label,
         }
     }
 }
 
+/// The children spawned for a [ProgressBar], rebuilt when it changes
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+struct ProgressBarLayout {
+    orientation: BarOrientation,
+    segments: u32,
+    label: bool,
+}
+
+/// Percent shown by the trail of a [ProgressBar], following its value
+#[derive(Component, Debug, Default)]
+struct TrailPercent(f32);
+
+/// A segment of a [ProgressBar], drawn with its background
+#[derive(Component)]
+struct ProgressBarSegment {
+    bar: Entity,
+}
+
+/// The filled part of a [ProgressBarSegment], or of its trail
+#[derive(Component)]
+#[require(Node, BackgroundColor)]
+struct ProgressBarFill {
+    bar: Entity,
+    segment: u32,
+    trail: bool,
+}
+
 #[derive(Component)]
-struct ProgressBarForeground {
-    min: Arc<Mutex<f32>>,
-    max: Arc<Mutex<f32>>,
-    value: Arc<Mutex<f32>>,
+struct ProgressBarLabel {
+    bar: Entity,
 }
 
-impl ProgressBarForeground {
-    fn new(data: &ProgressBar) -> Self {
-        ProgressBarForeground {
-            min: data.min.clone(),
-            max: data.max.clone(),
-            value: data.value.clone(),
+fn layout_progress_bars(
+    mut commands: Commands,
+    mut bars: Query<
+        (Entity, &ProgressBar, &mut Node, Option<&ProgressBarLayout>),
+        Changed<ProgressBar>,
+    >,
+) {
+    for (entity, bar, mut node, layout) in &mut bars {
+        let new_layout = bar.layout();
+        if layout == Some(&new_layout) {
+            continue;
         }
+        node.flex_direction = match bar.orientation {
+            BarOrientation::Horizontal => FlexDirection::Row,
+            BarOrientation::Vertical => FlexDirection::ColumnReverse,
+        };
+        node.column_gap = SEGMENT_GAP;
+        node.row_gap = SEGMENT_GAP;
+
+        commands
+            .entity(entity)
+            .insert((new_layout, BackgroundColor(Color::NONE)))
+            .despawn_related::<Children>()
+            .with_children(|parent| {
+                for segment in 0..new_layout.segments {
+                    parent
+                        .spawn((
+                            Name::new("ProgressBarSegment"),
+                            ProgressBarSegment { bar: entity },
+                            Node {
+                                flex_grow: 1.,
+                                flex_basis: Val::Px(0.),
+                                ..default()
+                            },
+                        ))
+                        .with_children(|parent| {
+                            for trail in [true, false] {
+                                parent.spawn(ProgressBarFill {
+                                    bar: entity,
+                                    segment,
+                                    trail,
+                                });
+                            }
+                        });
+                }
+                if new_layout.label {
+                    parent.spawn((
+                        Name::new("ProgressBarLabel"),
+                        ProgressBarLabel { bar: entity },
+                        Node {
+                            position_type: PositionType::Absolute,
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        children![(
+                            Text::default(),
+                            TextFont::from_font_size(14.0),
+                            TextColor(Color::WHITE),
+                        )],
+                    ));
+                }
+            });
     }
+}
 
-    fn percent(&self) -> f32 {
-        let min = self.min.lock().unwrap();
-        let max = self.max.lock().unwrap();
-        let value = self.value.lock().unwrap();
-        *value / (*max - *min)
+/// Move the trail smoothly down to the value, or up at once
+fn move_progress_bar_trails(time: Res<Time>, mut bars: Query<(&ProgressBar, &mut TrailPercent)>) {
+    for (bar, mut trail) in &mut bars {
+        let percent = bar.percent();
+        trail.0 = if bar.trail.is_none() || trail.0 <= percent {
+            percent
+        } else {
+            percent + (trail.0 - percent) * (-TRAIL_RATE * time.delta_secs()).exp()
+        };
     }
 }
 
-fn create_progress_bars(
-    mut commands: Commands,
-    mut query: Query<(Entity, &mut BackgroundColor, &ProgressBar), Added<ProgressBar>>,
+fn update_progress_bar_segments(
+    bars: Query<&ProgressBar>,
+    mut segments: Query<(&ProgressBarSegment, &mut BackgroundColor)>,
 ) {
-    for (entity, mut bkcolor, data) in query.iter_mut() {
-        // Set background
-        *bkcolor = data.background.into();
-
-        // add foreground
-        commands.entity(entity).with_children(|parent| {
-            // foreground
-            parent.spawn((
-                Node {
-                    width: Val::Percent(0.0),
-                    height: Val::Percent(100.0),
-                    ..default()
-                },
-                BackgroundColor(data.foreground),
-                ProgressBarForeground::new(data),
-            ));
-        });
+    for (segment, mut background) in &mut segments {
+        if let Ok(bar) = bars.get(segment.bar) {
+            background.set_if_neq(BackgroundColor(bar.background));
+        }
     }
 }
 
-fn update_progress_bars(mut child_query: Query<(&ProgressBarForeground, &mut Node)>) {
-    for (data, mut node) in child_query.iter_mut() {
-        node.width = Val::Percent(100.0 * data.percent());
+fn update_progress_bar_fills(
+    bars: Query<(&ProgressBar, &TrailPercent)>,
+    mut fills: Query<(&ProgressBarFill, &mut Node, &mut BackgroundColor)>,
+) {
+    for (fill, mut node, mut background) in &mut fills {
+        let Ok((bar, trail)) = bars.get(fill.bar) else {
+            continue;
+        };
+        let (percent, color) = match (fill.trail, bar.trail) {
+            (false, _) => (bar.percent(), bar.foreground),
+            (true, Some(color)) => (trail.0, color),
+            (true, None) => (0., Color::NONE),
+        };
+        // Part of this segment that is filled
+        let segments = bar.segment_count();
+        let filled = (percent * segments as f32 - fill.segment as f32).clamp(0., 1.);
+        let new_node = match bar.orientation {
+            BarOrientation::Horizontal => Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(0.),
+                width: Val::Percent(filled * 100.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            BarOrientation::Vertical => Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(0.),
+                width: Val::Percent(100.),
+                height: Val::Percent(filled * 100.),
+                ..default()
+            },
+        };
+        node.set_if_neq(new_node);
+        background.set_if_neq(BackgroundColor(color));
+    }
+}
+
+fn update_progress_bar_labels(
+    bars: Query<&ProgressBar>,
+    labels: Query<(&ProgressBarLabel, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (label, children) in &labels {
+        let Ok(bar) = bars.get(label.bar) else {
+            continue;
+        };
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            let value = format!("{:.0}/{:.0}", bar.value, bar.max);
+            if text.0 != value {
+                text.0 = value;
+            }
+        }
     }
 }
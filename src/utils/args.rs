@@ -0,0 +1,6 @@
+/// Value of a command line option, given as `<name> <value>`
+pub fn arg_value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != name);
+    args.next()?;
+    args.next()
+}
@@ -1,39 +1,77 @@
-use bevy::ecs::query::{QueryData, QueryFilter};
-use bevy::prelude::*;
+use bevy::{
+    ecs::{entity::Entities, query::QueryFilter, system::SystemParam},
+    prelude::*,
+};
 use bevy_rapier2d::prelude::*;
 
-/// Filter CollisionEvent::Started events
-pub fn start_event_filter(event: &CollisionEvent) -> Option<(&Entity, &Entity)> {
-    match event {
-        CollisionEvent::Started(e1, e2, _) => Some((e1, e2)),
-        _ => None,
-    }
+/// A [CollisionEvent] between an entity matching a filter `A` and one matching a filter `B`,
+/// see [Collisions]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CollisionPair {
+    /// The `A` and `B` entities started to collide
+    Started(Entity, Entity),
+    /// The `A` and `B` entities stopped to collide
+    Stopped(Entity, Entity),
 }
 
-/// QueryEither
+/// Reads the [CollisionEvent]s between entities matching the `A` and `B` filters,
+/// delivering the entities as `(A, B)` pairs, whatever their order in the event.
+///
+/// An entity removed from the world still matches both filters in a
+/// [CollisionEvent::Stopped] event, so the collision with it can be forgotten.
 ///
 /// Example:
 /// ```
-/// query1.iter().filter_map(query.get_either(e1, e2)).map(|(data, e1, e2)|{})
+/// fn open_chest(mut collisions: Collisions<With<Player>, With<Chest>>) {
+///     for (player_entity, chest_entity) in collisions.started() {}
+/// }
 /// ```
-pub trait QueryEither<'w, D>
-where
-    D: QueryData<ReadOnly = D>,
-{
-    /// get either `e1` or `e2`, returning a `([QueryData], [Entity from query], [other Entity])`
-    fn get_either(&'w self, e1: Entity, e2: Entity) -> Option<(D::Item<'w>, Entity, Entity)>;
+#[derive(SystemParam)]
+pub struct Collisions<'w, 's, A: QueryFilter + 'static, B: QueryFilter + 'static> {
+    events: EventReader<'w, 's, CollisionEvent>,
+    a: Query<'w, 's, (), A>,
+    b: Query<'w, 's, (), B>,
+    entities: &'w Entities,
 }
 
-impl<'w, D, F> QueryEither<'w, D> for Query<'w, '_, D, F>
-where
-    D: QueryData<ReadOnly = D>,
-    F: QueryFilter,
-{
-    fn get_either(&'w self, e1: Entity, e2: Entity) -> Option<(D::Item<'w>, Entity, Entity)> {
-        self.get(e1)
-            .map(|data| (data, e1, e2))
-            .or(self.get(e2).map(|data| (data, e2, e1)))
-            .ok()
+impl<A: QueryFilter + 'static, B: QueryFilter + 'static> Collisions<'_, '_, A, B> {
+    pub fn read(&mut self) -> impl Iterator<Item = CollisionPair> + '_ {
+        let Self {
+            events,
+            a,
+            b,
+            entities,
+        } = self;
+        events.read().filter_map(|event| {
+            let (e1, e2, removed) = match *event {
+                CollisionEvent::Started(e1, e2, _) => (e1, e2, false),
+                CollisionEvent::Stopped(e1, e2, flags) => {
+                    (e1, e2, flags.contains(CollisionEventFlags::REMOVED))
+                }
+            };
+            let removed = |entity: Entity| removed && !entities.contains(entity);
+            let is_a = |entity: Entity| a.contains(entity) || removed(entity);
+            let is_b = |entity: Entity| b.contains(entity) || removed(entity);
+            let (entity_a, entity_b) = if is_a(e1) && is_b(e2) {
+                (e1, e2)
+            } else if is_a(e2) && is_b(e1) {
+                (e2, e1)
+            } else {
+                return None;
+            };
+            Some(match event {
+                CollisionEvent::Started(..) => CollisionPair::Started(entity_a, entity_b),
+                CollisionEvent::Stopped(..) => CollisionPair::Stopped(entity_a, entity_b),
+            })
+        })
+    }
+
+    /// The `(A, B)` pairs that started to collide
+    pub fn started(&mut self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.read().filter_map(|pair| match pair {
+            CollisionPair::Started(a, b) => Some((a, b)),
+            CollisionPair::Stopped(..) => None,
+        })
     }
 }
 
@@ -52,48 +90,3 @@ where
         self == &v1 || self == &v2
     }
 }
-
-//
-// TODO: finish it
-// Trait to filter a collision with 2 queries
-//
-// pub trait CollisionEventFilter {
-//     fn start<'w, D1, F1, D2, F2>(
-//         &self,
-//         q1: Query<'w, '_, D1, F1>,
-//         q2: Query<'w, '_, D2, F2>,
-//     ) -> Option<(ROQueryItem<'w, D1>, ROQueryItem<'w, D2>)>
-//     where
-//         D1: QueryData<ReadOnly = D1>,
-//         F1: QueryFilter,
-//         D2: QueryData,
-//         F2: QueryFilter;
-// }
-
-// impl CollisionEventFilter for CollisionEvent {
-//     fn start<'w, D1, F1, D2, F2>(
-//         &self,
-//         q1: Query<'w, '_, D1, F1>,
-//         q2: Query<'w, '_, D2, F2>,
-//     ) -> Option<(ROQueryItem<'w, D1>, ROQueryItem<'w, D2>)>
-//     where
-//         D1: QueryData<ReadOnly = D1>,
-//         F1: QueryFilter,
-//         D2: QueryData,
-//         F2: QueryFilter,
-//     {
-//         if let CollisionEvent::Started(e1, e2, _) = self {
-//             let res = q1
-//                 .get(*e1)
-//                 .map(move |r1| (r1, q2.get(*e2)))
-//                 .or(q1.get(*e2).map(|r1| (r1, q2.get(*e1))));
-
-//             match res {
-//                 Ok((r1, Ok(r2))) => Some((r1, r2)),
-//                 _ => None,
-//             }
-//         } else {
-//             None
-//         }
-//     }
-// }
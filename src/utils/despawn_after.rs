@@ -8,7 +8,6 @@ pub struct DespawnAfter {
 }
 
 impl DespawnAfter {
-    #[allow(dead_code)]
     pub fn new(duration: Duration) -> Self {
         DespawnAfter {
             timer: Timer::new(duration, TimerMode::Once),
@@ -23,7 +22,7 @@ impl DespawnAfter {
 }
 
 pub fn despawn_after_plugin(app: &mut App) {
-    app.add_systems(Update, despawn_entity);
+    app.add_systems(FixedUpdate, despawn_entity);
 }
 
 fn despawn_entity(
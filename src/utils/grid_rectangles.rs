@@ -0,0 +1,81 @@
+//! Merge the cells of a grid into rectangles.
+//!
+//! This module only depends on math types, so it can be tested and benchmarked
+//! without a Bevy world.
+
+use bevy::math::IRect;
+use bevy_ecs_ldtk::prelude::GridCoords;
+use std::collections::{HashMap, HashSet};
+
+/// Represents a wide wall that is 1 tile tall
+#[derive(Clone, Eq, PartialEq, Debug, Default, Hash)]
+struct Plate {
+    left: i32,
+    right: i32,
+}
+
+/// Returns rectangles exactly covering the `cells` of a `width` x `height` grid,
+/// without overlapping. Cells outside of the grid are ignored.
+///
+/// The algorithm used here is a nice compromise between simplicity, speed,
+/// and a small number of rectangles.
+/// In basic terms, it will:
+/// 1. combine cells into flat "plates" in each individual row
+/// 2. combine the plates into rectangles across multiple rows wherever possible
+///
+/// The rectangles bounds are inclusive: a single cell `(x, y)` gives
+/// `IRect::new(x, y, x, y)`.
+pub fn grid_rectangles(cells: &HashSet<GridCoords>, width: i32, height: i32) -> Vec<IRect> {
+    let mut rectangles = vec![];
+
+    // combine cells into flat "plates" in each individual row
+    let mut plate_stack: Vec<Vec<Plate>> = Vec::new();
+
+    for y in 0..height {
+        let mut row_plates: Vec<Plate> = Vec::new();
+        let mut plate_start = None;
+
+        // + 1 to the width so the algorithm "terminates" plates that touch the right edge
+        for x in 0..width + 1 {
+            let is_cell = x < width && cells.contains(&GridCoords { x, y });
+            match (plate_start, is_cell) {
+                (Some(s), false) => {
+                    row_plates.push(Plate {
+                        left: s,
+                        right: x - 1,
+                    });
+                    plate_start = None;
+                }
+                (None, true) => plate_start = Some(x),
+                _ => (),
+            }
+        }
+        plate_stack.push(row_plates);
+    }
+
+    // combine "plates" into rectangles across multiple rows
+    let mut rect_builder: HashMap<Plate, IRect> = HashMap::new();
+    let mut prev_row: Vec<Plate> = Vec::new();
+
+    // an extra empty row so the algorithm "finishes" the rects that touch the top edge
+    plate_stack.push(Vec::new());
+
+    for (y, current_row) in plate_stack.into_iter().enumerate() {
+        for prev_plate in &prev_row {
+            if !current_row.contains(prev_plate) {
+                // remove the finished rect so that the same plate in the future starts a new rect
+                if let Some(rect) = rect_builder.remove(prev_plate) {
+                    rectangles.push(rect);
+                }
+            }
+        }
+        for plate in &current_row {
+            rect_builder
+                .entry(plate.clone())
+                .and_modify(|rect| rect.max.y += 1)
+                .or_insert(IRect::new(plate.left, y as i32, plate.right, y as i32));
+        }
+        prev_row = current_row;
+    }
+    rectangles
+}
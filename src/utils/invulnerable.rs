@@ -7,7 +7,10 @@ pub struct InvulnerabilityPlugin;
 
 impl Plugin for InvulnerabilityPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (invulnerability_started, invulnerability_finished));
+        app.add_systems(
+            FixedUpdate,
+            (invulnerability_started, invulnerability_finished),
+        );
     }
 }
 
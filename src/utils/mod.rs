@@ -1,5 +1,7 @@
+pub mod args;
 pub mod blink;
 pub mod collisions;
 pub mod despawn_after;
+pub mod grid_rectangles;
 pub mod invulnerable;
 pub mod iter_ext;